@@ -4,6 +4,10 @@
 //!
 //! It ultilizes the [Genius](https://genius.com) website and its APIs to get lyric data.
 //!
+//! Multiple lyrics sources are also available through the [`LyricsProvider`] trait:
+//! - [`Client`]: unsynced lyrics from [Genius](https://genius.com)
+//! - [`LrclibClient`]: synced lyrics from an [LRCLIB](https://lrclib.net)-compatible API
+//!
 //! ## Example
 //!
 //! ```rust
@@ -27,6 +31,31 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Example using a lyrics provider
+//!
+//! ```rust
+//! # use anyhow::Result;
+//! use lyric_finder::{LrclibClient, LyricsProvider, TrackQuery};
+//!
+//! # async fn run() -> Result<()> {
+//! let provider = LrclibClient::new();
+//! let query = TrackQuery::new("shape of you", "ed sheeran");
+//! if let Some(lyrics) = provider.find_lyrics(&query).await? {
+//!     for (t, line) in lyrics.lines {
+//!         println!("[{}ms] {line}", t.as_millis());
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod lrc;
+mod lrclib;
+mod provider;
+
+pub use lrclib::LrclibClient;
+pub use provider::{Lyrics, LyricsProvider, TrackQuery};
 
 const SEARCH_BASE_URL: &str = "https://genius.com/api/search";
 
@@ -145,6 +174,20 @@ impl Default for Client {
     }
 }
 
+#[async_trait::async_trait]
+impl LyricsProvider for Client {
+    fn name(&self) -> &'static str {
+        "Genius"
+    }
+
+    async fn find_lyrics(&self, query: &TrackQuery) -> anyhow::Result<Option<Lyrics>> {
+        match self.get_lyric(&query.to_search_query()).await? {
+            LyricResult::Some { lyric, .. } => Ok(Some(Lyrics::from_unsynced_text(&lyric))),
+            LyricResult::None => Ok(None),
+        }
+    }
+}
+
 /// Returns `query` without `remaster` & `remix` information from track/artist query.
 /// Returned value is lowercase.
 /// These caused wildly invalid lyrics to be found.
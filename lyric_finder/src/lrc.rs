@@ -0,0 +1,219 @@
+//! Parser and formatter for the [LRC](https://en.wikipedia.org/wiki/LRC_(file_format)) lyrics format
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::Lyrics;
+
+/// Parse the content of an LRC file.
+///
+/// Supported features:
+/// - multiple timestamps per line, e.g `[00:12.00][01:15.30]chorus`
+/// - the `[offset:<ms>]` tag, a positive offset makes the lyrics show up sooner
+///
+/// Other ID tags (`[ar:...]`, `[ti:...]`, etc) are ignored.
+/// Content without any timestamped line is returned as unsynced lyrics.
+#[must_use]
+pub fn parse(content: &str) -> Lyrics {
+    let mut offset_ms = 0_i64;
+    let mut timed_lines = Vec::new();
+    let mut plain_lines = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        let mut rest = line;
+        let mut timestamps = Vec::new();
+
+        while let Some(tag) = rest.strip_prefix('[') {
+            let Some(end) = tag.find(']') else { break };
+            let (tag, remaining) = (&tag[..end], &tag[end + 1..]);
+            if let Some(t) = parse_timestamp(tag) {
+                timestamps.push(t);
+            } else if let Some(value) = tag.strip_prefix("offset:") {
+                offset_ms = value.trim().parse().unwrap_or(offset_ms);
+            } else if timestamps.is_empty() && is_id_tag(tag) {
+                // skip ID tags
+            } else {
+                break;
+            }
+            rest = remaining;
+        }
+
+        if timestamps.is_empty() {
+            if rest.len() == line.len() {
+                plain_lines.push(line.to_string());
+            }
+        } else {
+            let text = rest.trim().to_string();
+            timed_lines.extend(timestamps.into_iter().map(|t| (t, text.clone())));
+        }
+    }
+
+    if timed_lines.is_empty() {
+        // trim leading and trailing empty lines of unsynced lyrics
+        let start = plain_lines.iter().position(|l| !l.is_empty());
+        let end = plain_lines.iter().rposition(|l| !l.is_empty());
+        let lines = match (start, end) {
+            (Some(start), Some(end)) => &plain_lines[start..=end],
+            _ => &[],
+        };
+        return Lyrics::from_unsynced_text(&lines.join("\n"));
+    }
+
+    let mut lines = timed_lines
+        .into_iter()
+        .map(|(t, text)| {
+            let t = (t as i64 - offset_ms).max(0);
+            (Duration::from_millis(t as u64), text)
+        })
+        .collect::<Vec<_>>();
+    lines.sort_by_key(|l| l.0);
+
+    Lyrics {
+        lines,
+        synced: true,
+    }
+}
+
+/// Format lyrics into the content of an LRC file.
+///
+/// `tags` are written as ID tags (e.g `("ar", "artist")` becomes `[ar:artist]`)
+/// before the lyrics' lines. Lines of unsynced lyrics are written without timestamps.
+#[must_use]
+pub fn format(lyrics: &Lyrics, tags: &[(&str, &str)]) -> String {
+    let mut content = String::new();
+    for (key, value) in tags {
+        let _ = writeln!(content, "[{key}:{value}]");
+    }
+    for (t, line) in &lyrics.lines {
+        if lyrics.synced {
+            let centis = t.as_millis() / 10;
+            let _ = write!(
+                content,
+                "[{:02}:{:02}.{:02}]",
+                centis / 6000,
+                (centis / 100) % 60,
+                centis % 100
+            );
+        }
+        let _ = writeln!(content, "{line}");
+    }
+    content
+}
+
+/// Parse a `mm:ss.xx` (or `mm:ss:xx`, `mm:ss`) timestamp into milliseconds
+fn parse_timestamp(s: &str) -> Option<u64> {
+    let (minutes, rest) = s.split_once(':')?;
+    let minutes = minutes.trim().parse::<u64>().ok()?;
+
+    let (seconds, fraction) = match rest.split_once(['.', ':']) {
+        Some((seconds, fraction)) => (seconds, fraction),
+        None => (rest, ""),
+    };
+    let seconds = seconds.trim().parse::<u64>().ok()?;
+
+    let fraction_ms = if fraction.is_empty() {
+        0
+    } else {
+        if !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        // a fraction can be in hundredths (`.xx`) or thousandths (`.xxx`) of a second
+        let digits = fraction.len().min(3);
+        let value = fraction[..digits].parse::<u64>().ok()?;
+        value * 10_u64.pow(3 - digits as u32)
+    };
+
+    Some((minutes * 60 + seconds) * 1000 + fraction_ms)
+}
+
+fn is_id_tag(tag: &str) -> bool {
+    tag.split_once(':')
+        .is_some_and(|(key, _)| !key.is_empty() && key.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{format, parse};
+    use crate::Lyrics;
+
+    fn synced(lines: &[(u64, &str)]) -> Lyrics {
+        Lyrics {
+            lines: lines
+                .iter()
+                .map(|&(ms, line)| (Duration::from_millis(ms), line.to_string()))
+                .collect(),
+            synced: true,
+        }
+    }
+
+    #[test]
+    fn round_trip_centiseconds() {
+        let content = "[ti:title]\n[ar:artist]\n[00:01.50]first\n[00:12.34]second\n[01:05.00]\n";
+        let lyrics = parse(content);
+        assert_eq!(
+            lyrics,
+            synced(&[(1500, "first"), (12340, "second"), (65000, "")])
+        );
+        assert_eq!(
+            format(&lyrics, &[("ti", "title"), ("ar", "artist")]),
+            content
+        );
+    }
+
+    #[test]
+    fn round_trip_milliseconds() {
+        let lyrics = parse("[00:01.505]first\n[02:03.456]second");
+        assert_eq!(lyrics, synced(&[(1505, "first"), (123_456, "second")]));
+
+        // timestamps are formatted in hundredths of a second
+        let content = format(&lyrics, &[]);
+        assert_eq!(content, "[00:01.50]first\n[02:03.45]second\n");
+        assert_eq!(
+            parse(&content),
+            synced(&[(1500, "first"), (123_450, "second")])
+        );
+    }
+
+    #[test]
+    fn round_trip_multiple_timestamps() {
+        let lyrics = parse("[00:10.00][00:30.00]chorus\n[00:20.00]verse");
+        assert_eq!(
+            lyrics,
+            synced(&[(10000, "chorus"), (20000, "verse"), (30000, "chorus")])
+        );
+
+        // each timestamp is written on its own line
+        let content = format(&lyrics, &[]);
+        assert_eq!(
+            content,
+            "[00:10.00]chorus\n[00:20.00]verse\n[00:30.00]chorus\n"
+        );
+        assert_eq!(parse(&content), lyrics);
+    }
+
+    #[test]
+    fn round_trip_offset() {
+        // a positive offset makes the lyrics show up sooner, without going below zero
+        let lyrics = parse("[offset:500]\n[00:00.20]first\n[00:02.00]second");
+        assert_eq!(lyrics, synced(&[(0, "first"), (1500, "second")]));
+
+        // the offset is applied to the formatted timestamps
+        let content = format(&lyrics, &[]);
+        assert_eq!(content, "[00:00.00]first\n[00:01.50]second\n");
+        assert_eq!(parse(&content), lyrics);
+
+        let lyrics = parse("[offset:-250]\n[00:01.00]first");
+        assert_eq!(lyrics, synced(&[(1250, "first")]));
+    }
+
+    #[test]
+    fn round_trip_unsynced() {
+        let content = "first\nsecond\n";
+        let lyrics = parse(&format!("\n[ar:artist]\n{content}\n"));
+        assert_eq!(lyrics, Lyrics::from_unsynced_text(content));
+        assert_eq!(format(&lyrics, &[]), content);
+    }
+}
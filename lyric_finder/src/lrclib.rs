@@ -0,0 +1,124 @@
+//! A client for [LRCLIB](https://lrclib.net)-compatible lyrics APIs
+
+use serde::Deserialize;
+
+use crate::{lrc, Lyrics, LyricsProvider, TrackQuery};
+
+const DEFAULT_BASE_URL: &str = "https://lrclib.net";
+
+/// A lyrics provider using an LRCLIB-compatible HTTP API
+pub struct LrclibClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Record {
+    #[serde(default)]
+    instrumental: bool,
+    plain_lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+}
+
+impl LrclibClient {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_http_client(&reqwest::Client::new(), DEFAULT_BASE_URL)
+    }
+
+    /// Construct a client reusing an existing http client.
+    ///
+    /// `base_url` is the API's base URL without the `/api` path, e.g `https://lrclib.net`.
+    #[must_use]
+    pub fn from_http_client(http: &reqwest::Client, base_url: &str) -> Self {
+        Self {
+            http: http.clone(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Get the lyrics record matching exactly a track's signature.
+    ///
+    /// The API requires the track's album and duration for an exact lookup,
+    /// `None` is returned if either of them is unknown.
+    async fn get(&self, query: &TrackQuery) -> anyhow::Result<Option<Record>> {
+        let (Some(album), Some(duration)) = (&query.album, query.duration) else {
+            return Ok(None);
+        };
+
+        let response = self
+            .http
+            .get(format!("{}/api/get", self.base_url))
+            .query(&[
+                ("track_name", query.track.as_str()),
+                ("artist_name", query.artists.as_str()),
+                ("album_name", album.as_str()),
+                ("duration", &duration.as_secs().to_string()),
+            ])
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.json::<Record>().await?))
+    }
+
+    /// Search lyrics records matching a track's name and artists
+    async fn search(&self, query: &TrackQuery) -> anyhow::Result<Vec<Record>> {
+        Ok(self
+            .http
+            .get(format!("{}/api/search", self.base_url))
+            .query(&[
+                ("track_name", query.track.as_str()),
+                ("artist_name", query.artists.as_str()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<Record>>()
+            .await?)
+    }
+}
+
+impl Default for LrclibClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Record {
+    /// Convert the record into lyrics, preferring synced lyrics over plain lyrics
+    fn into_lyrics(self) -> Option<Lyrics> {
+        if self.instrumental {
+            return None;
+        }
+        if let Some(synced) = self.synced_lyrics.filter(|l| !l.trim().is_empty()) {
+            return Some(lrc::parse(&synced));
+        }
+        self.plain_lyrics
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| Lyrics::from_unsynced_text(l.trim()))
+    }
+}
+
+#[async_trait::async_trait]
+impl LyricsProvider for LrclibClient {
+    fn name(&self) -> &'static str {
+        "LRCLIB"
+    }
+
+    async fn find_lyrics(&self, query: &TrackQuery) -> anyhow::Result<Option<Lyrics>> {
+        log::debug!("find lyrics on LRCLIB: query={query:?}");
+
+        if let Some(lyrics) = self.get(query).await?.and_then(Record::into_lyrics) {
+            return Ok(Some(lyrics));
+        }
+
+        // fall back to a search, prefering records with synced lyrics
+        let mut records = self.search(query).await?;
+        records.sort_by_key(|r| r.synced_lyrics.is_none());
+        Ok(records.into_iter().find_map(Record::into_lyrics))
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+/// Information about a track used to look up its lyrics
+#[derive(Debug, Clone, Default)]
+pub struct TrackQuery {
+    pub track: String,
+    /// Artists' names, separated by commas
+    pub artists: String,
+    pub album: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+impl TrackQuery {
+    #[must_use]
+    pub fn new(track: impl Into<String>, artists: impl Into<String>) -> Self {
+        Self {
+            track: track.into(),
+            artists: artists.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Get a free-text search query for the track
+    #[must_use]
+    pub fn to_search_query(&self) -> String {
+        format!("{} {}", self.track, self.artists)
+    }
+}
+
+/// A track's lyrics returned by a [`LyricsProvider`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lyrics {
+    /// Timestamped lines, sorted by their timestamps.
+    /// Lines of unsynced lyrics all have a zero timestamp.
+    pub lines: Vec<(Duration, String)>,
+    /// Whether the lines' timestamps are meaningful
+    pub synced: bool,
+}
+
+impl Lyrics {
+    /// Construct unsynced lyrics from a plain text
+    #[must_use]
+    pub fn from_unsynced_text(text: &str) -> Self {
+        Self {
+            lines: text
+                .lines()
+                .map(|l| (Duration::ZERO, l.to_string()))
+                .collect(),
+            synced: false,
+        }
+    }
+}
+
+/// A source of lyrics
+#[async_trait::async_trait]
+pub trait LyricsProvider: Send + Sync {
+    /// The provider's display name
+    fn name(&self) -> &'static str;
+
+    /// Find the lyrics of a track, return `None` if the provider has no lyrics for the track
+    async fn find_lyrics(&self, query: &TrackQuery) -> anyhow::Result<Option<Lyrics>>;
+}
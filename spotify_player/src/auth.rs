@@ -65,6 +65,8 @@ impl AuthConfig {
             None
         };
 
+        // the audio cache's size limit is handled by the application (see `client::prune_audio_cache`)
+        // to exclude audio files of pinned contexts from the cache's eviction
         let cache = Cache::new(
             Some(configs.cache_folder.clone()),
             None,
@@ -4,6 +4,7 @@ use std::{
     fs::{create_dir_all, remove_dir_all},
     io::Write,
     net::SocketAddr,
+    time::Duration,
 };
 
 use anyhow::{Context as _, Result};
@@ -14,17 +15,22 @@ use tracing::Instrument;
 use crate::{
     cli::Request,
     client::{Client, PlayerRequest},
-    config::get_cache_folder_path,
+    config::{self, get_cache_folder_path},
+    ex_command::{self, ExCommand, OpenTarget},
     state::{
-        AlbumId, ArtistId, Context, ContextId, Id, PlayableId, Playback, PlaybackMetadata,
-        PlaylistId, SharedState, TrackId,
+        AlbumId, ArtistId, Context, ContextId, ContextPageType, Id, PageState, PlayableId,
+        Playback, PlaybackMetadata, PlaylistId, SharedState, SleepTimer, TrackId,
     },
 };
 use rspotify::prelude::{BaseClient, OAuthClient};
 
 use super::{
-    Command, Deserialize, GetRequest, IdOrName, ItemId, ItemType, Key, PlaylistCommand, Response,
-    Serialize, MAX_REQUEST_SIZE,
+    framing::{
+        self, CHUNK_TIMEOUT, CHUNK_WINDOW_SIZE, MAX_CHUNK_PAYLOAD_SIZE, MAX_CHUNK_RETRIES,
+        MAX_RESPONSE_SIZE,
+    },
+    AlarmCommand, Command, Deserialize, GetRequest, IdOrName, ItemId, ItemType, Key,
+    PlaylistCommand, Response, Serialize, SleepCommand, MAX_REQUEST_SIZE,
 };
 
 pub async fn start_socket(client: Client, socket: UdpSocket, state: Option<SharedState>) {
@@ -51,7 +57,10 @@ pub async fn start_socket(client: Client, socket: UdpSocket, state: Option<Share
 
                 let span = tracing::info_span!("socket_request", request = ?request, dest_addr = ?dest_addr);
 
-                async {
+                // requests are handled concurrently, so that a slow request doesn't block others
+                let client = client.clone();
+                let state = state.clone();
+                let task = async move {
                     let response =
                         match handle_socket_request(&client, state.as_ref(), request).await {
                             Err(err) => {
@@ -61,33 +70,62 @@ pub async fn start_socket(client: Client, socket: UdpSocket, state: Option<Share
                             }
                             Ok(data) => Response::Ok(data),
                         };
-                    send_response(response, &socket, dest_addr)
-                        .await
-                        .unwrap_or_default();
+                    if let Err(err) = send_response(response, dest_addr).await {
+                        tracing::warn!("Failed to send the socket response: {err:#}");
+                    }
 
                     tracing::info!("Successfully handled the socket request.",);
-                }
-                .instrument(span)
-                .await;
+                };
+                tokio::task::spawn(task.instrument(span));
             }
         }
     }
 }
 
-async fn send_response(
-    response: Response,
-    socket: &UdpSocket,
-    dest_addr: SocketAddr,
-) -> Result<()> {
-    let data = serde_json::to_vec(&response)?;
+async fn send_response(response: Response, dest_addr: SocketAddr) -> Result<()> {
+    let mut data = serde_json::to_vec(&response)?;
+    if data.len() > MAX_RESPONSE_SIZE {
+        let msg = format!("Bad request: the response is larger than {MAX_RESPONSE_SIZE} bytes");
+        data = serde_json::to_vec(&Response::Err(msg.into_bytes()))?;
+    }
 
     // as the result data can be large and may not be sent in a single UDP datagram,
     // split it into smaller chunks
-    for chunk in data.chunks(4096) {
-        socket.send_to(chunk, dest_addr).await?;
+    let chunks = data.chunks(MAX_CHUNK_PAYLOAD_SIZE).collect::<Vec<_>>();
+    let total = u32::try_from(chunks.len()).context("response is too large")?;
+
+    // send the response from a dedicated socket, so that the receiver's
+    // acknowledgements are not mixed with requests to the client's socket
+    let socket = UdpSocket::bind("127.0.0.1:0").await?;
+
+    let mut acked = 0;
+    let mut retries = 0;
+    let mut buf = [0; 4];
+    while acked < total {
+        // send the unacknowledged chunks of the current window
+        let window_end = std::cmp::min((acked / CHUNK_WINDOW_SIZE + 1) * CHUNK_WINDOW_SIZE, total);
+        for seq in acked..window_end {
+            let chunk = framing::encode_chunk(seq, total, chunks[seq as usize]);
+            socket.send_to(&chunk, dest_addr).await?;
+        }
+
+        // wait for an acknowledgement of the window's chunks
+        if let Ok(res) = tokio::time::timeout(CHUNK_TIMEOUT, socket.recv_from(&mut buf)).await {
+            let (n_bytes, addr) = res?;
+            if let Some(ack) = framing::decode_ack(&buf[..n_bytes]).filter(|_| addr == dest_addr) {
+                if ack > acked {
+                    acked = std::cmp::min(ack, total);
+                    retries = 0;
+                    continue;
+                }
+            }
+        }
+
+        retries += 1;
+        if retries > MAX_CHUNK_RETRIES {
+            anyhow::bail!("response is truncated: only {acked} of {total} chunks are acknowledged");
+        }
     }
-    // send an empty buffer to indicate end of chunk
-    socket.send_to(&[], dest_addr).await?;
     Ok(())
 }
 
@@ -105,7 +143,7 @@ async fn current_playback(
     }
 }
 
-async fn handle_socket_request(
+pub(super) async fn handle_socket_request(
     client: &Client,
     state: Option<&SharedState>,
     request: super::Request,
@@ -124,24 +162,7 @@ async fn handle_socket_request(
             Ok(Vec::new())
         }
         Request::Connect(data) => {
-            let id = match data {
-                IdOrName::Id(id) => id,
-                IdOrName::Name(name) => {
-                    let devices = client.device().await?;
-                    match devices
-                        .into_iter()
-                        .find(|d| d.name == name)
-                        .and_then(|d| d.id)
-                    {
-                        Some(id) => id,
-                        None => {
-                            anyhow::bail!("No device with name={name} found");
-                        }
-                    }
-                }
-            };
-
-            client.transfer_playback(&id, None).await?;
+            connect_device(client, data).await?;
             Ok(Vec::new())
         }
         Request::Like { unlike } => {
@@ -174,9 +195,170 @@ async fn handle_socket_request(
             let resp = handle_search_request(client, query).await?;
             Ok(resp)
         }
+        Request::Sleep(command) => {
+            let state = state.context("the sleep timer requires a running application")?;
+            let resp = handle_sleep_request(client, state, command).await?;
+            Ok(resp.into_bytes())
+        }
+        Request::Alarm(command) => {
+            let state = state.context("alarms require a running application")?;
+            let resp = handle_alarm_request(state, command)?;
+            Ok(resp.into_bytes())
+        }
+        Request::Exec(command) => handle_exec_request(client, state, command).await,
+        Request::Subscribe => {
+            anyhow::bail!("subscribing to the player's state events requires the Unix socket")
+        }
     }
 }
 
+async fn connect_device(client: &Client, data: IdOrName) -> Result<()> {
+    let id = match data {
+        IdOrName::Id(id) => id,
+        IdOrName::Name(name) => client.find_device(&name).await?.id,
+    };
+
+    client.transfer_playback(&id, None).await?;
+    Ok(())
+}
+
+/// Handle a command line's command, which is handled the same as the equivalent CLI command.
+/// Commands interacting with the application's UI require a running application.
+async fn handle_exec_request(
+    client: &Client,
+    state: Option<&SharedState>,
+    command: ExCommand,
+) -> Result<Vec<u8>> {
+    let command = match command {
+        ExCommand::Volume { percent, is_offset } => Command::Volume { percent, is_offset },
+        ExCommand::Seek {
+            position_ms,
+            is_offset: true,
+        } => Command::Seek(position_ms),
+        ExCommand::Seek {
+            position_ms,
+            is_offset: false,
+        } => Command::SeekTo(position_ms),
+        ExCommand::Search(query) => return handle_search_request(client, query).await,
+        ExCommand::Device(name) => {
+            connect_device(client, IdOrName::Name(name)).await?;
+            return Ok(Vec::new());
+        }
+        ExCommand::Open(link) => match OpenTarget::from_link(&link)? {
+            OpenTarget::Track(id) => Command::StartTrack(IdOrName::Id(id.id().to_string())),
+            OpenTarget::Context(id) => {
+                let state = state.context("opening a context requires a running application")?;
+                state.ui.lock().new_page(PageState::Context {
+                    id: None,
+                    context_page_type: ContextPageType::Browsing(id),
+                    state: None,
+                });
+                return Ok(Vec::new());
+            }
+        },
+        ExCommand::Theme(name) => {
+            let state = state.context("switching theme requires a running application")?;
+            state.ui.lock().theme = ex_command::find_theme(&name)?;
+            return Ok(Vec::new());
+        }
+    };
+
+    handle_playback_request(client, state, command).await?;
+    Ok(Vec::new())
+}
+
+async fn handle_sleep_request(
+    client: &Client,
+    state: &SharedState,
+    command: SleepCommand,
+) -> Result<String> {
+    let (resp, old_timer) = {
+        let mut player = state.player.write();
+        match command {
+            SleepCommand::Set {
+                mode,
+                fade_out_secs,
+            } => {
+                let fade_out = Duration::from_secs(
+                    fade_out_secs
+                        .unwrap_or(config::get_config().app_config.sleep_timer_fade_out_secs),
+                );
+                let timer = SleepTimer::new(mode, fade_out, &player);
+                (
+                    format!("Sleep timer is set to {mode}"),
+                    player.sleep_timer.replace(timer),
+                )
+            }
+            SleepCommand::Cancel => (
+                "Sleep timer is cancelled".to_string(),
+                player.sleep_timer.take(),
+            ),
+            SleepCommand::Status => {
+                let resp = match player.sleep_timer {
+                    None => "No sleep timer is set".to_string(),
+                    Some(ref timer) => match timer.remaining(&player, &state.data.read()) {
+                        Some(remaining) => format!(
+                            "Sleep timer is set to {} ({} remaining)",
+                            timer.mode,
+                            crate::utils::format_duration(
+                                &chrono::Duration::from_std(remaining).unwrap_or_default()
+                            )
+                        ),
+                        None => format!("Sleep timer is set to {}", timer.mode),
+                    },
+                };
+                return Ok(resp);
+            }
+        }
+    };
+
+    // restore the volume faded out by the replaced sleep timer
+    if let Some(volume) = old_timer.and_then(|t| t.volume_before_fade_out) {
+        handle_playback_request(
+            client,
+            Some(state),
+            Command::Volume {
+                percent: volume.try_into()?,
+                is_offset: false,
+            },
+        )
+        .await?;
+    }
+
+    Ok(resp)
+}
+
+fn handle_alarm_request(state: &SharedState, command: AlarmCommand) -> Result<String> {
+    let mut player = state.player.write();
+    Ok(match command {
+        AlarmCommand::Add(alarm) => {
+            alarm.time_of_day()?;
+            ContextId::from_uri(&alarm.context_uri)?;
+            let resp = format!("Added an alarm at {} for {}", alarm.time, alarm.context_uri);
+            player.alarms.push(alarm);
+            resp
+        }
+        AlarmCommand::List => {
+            let mut resp = String::new();
+            for alarm in &player.alarms {
+                writeln!(
+                    resp,
+                    "{} {}{}",
+                    alarm.time,
+                    alarm.context_uri,
+                    if alarm.shuffle { " (shuffle)" } else { "" }
+                )?;
+            }
+            resp
+        }
+        AlarmCommand::Clear => {
+            let resp = format!("Removed {} alarms", player.alarms.len());
+            player.alarms.clear();
+            resp
+        }
+    })
+}
+
 async fn handle_get_key_request(
     client: &Client,
     state: Option<&SharedState>,
@@ -432,6 +614,9 @@ async fn handle_playback_request(
                 progress + chrono::Duration::try_milliseconds(position_offset_ms).unwrap(),
             )
         }
+        Command::SeekTo(position_ms) => {
+            PlayerRequest::SeekTrack(chrono::Duration::try_milliseconds(position_ms).unwrap())
+        }
     };
 
     if let Some(state) = state {
@@ -1,7 +1,7 @@
 use clap::{builder::EnumValueParser, value_parser, Arg, ArgAction, ArgGroup, Command};
 use clap_complete::Shell;
 
-use super::{ContextType, ItemType, Key};
+use super::{CacheKind, ContextType, ItemType, Key};
 
 pub fn init_connect_subcommand() -> Command {
     add_id_or_name_group(Command::new("connect").about("Connect to a Spotify device"))
@@ -132,6 +132,96 @@ pub fn init_search_command() -> Command {
         .arg(Arg::new("query").help("Search query").required(true))
 }
 
+pub fn init_exec_command() -> Command {
+    Command::new("exec")
+        .about("Run a command line's command, e.g. `exec volume 40` or `exec seek 1:23`")
+        .arg(
+            Arg::new("command")
+                .help("Command with its arguments")
+                .required(true)
+                .num_args(1..)
+                .trailing_var_arg(true)
+                .allow_hyphen_values(true),
+        )
+}
+
+#[cfg(unix)]
+pub fn init_subscribe_command() -> Command {
+    Command::new("subscribe")
+        .about("Subscribe to the player's state events, printed as newline-delimited JSON")
+}
+
+pub fn init_sleep_command() -> Command {
+    Command::new("sleep")
+        .about("Set the sleep timer, which pauses the playback when it ends. Show the sleep timer's status if no argument is specified")
+        .arg(
+            Arg::new("minutes")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("Pause the playback after the given number of minutes"),
+        )
+        .arg(
+            Arg::new("end-of-track")
+                .long("end-of-track")
+                .action(ArgAction::SetTrue)
+                .help("Pause the playback at the end of the current track"),
+        )
+        .arg(
+            Arg::new("end-of-context")
+                .long("end-of-context")
+                .action(ArgAction::SetTrue)
+                .help("Pause the playback at the end of the current context"),
+        )
+        .arg(
+            Arg::new("cancel")
+                .long("cancel")
+                .action(ArgAction::SetTrue)
+                .help("Cancel the sleep timer"),
+        )
+        .group(
+            ArgGroup::new("mode")
+                .args(["minutes", "end-of-track", "end-of-context", "cancel"])
+                .required(false),
+        )
+        .arg(
+            Arg::new("fade-out")
+                .long("fade-out")
+                .value_name("SECS")
+                .value_parser(value_parser!(u64))
+                .requires("mode")
+                .conflicts_with("cancel")
+                .help("Fade out the volume during the given number of seconds before pausing the playback"),
+        )
+}
+
+pub fn init_alarm_subcommand() -> Command {
+    Command::new("alarm")
+        .about("Manage alarms starting a context's playback at a time of day")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("add")
+                .about("Add an alarm until the application exits")
+                .arg(
+                    Arg::new("time")
+                        .help("Time of day in the HH:MM format")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("context_uri")
+                        .help("URI of the playlist, album, artist or show to start")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("shuffle")
+                        .short('s')
+                        .long("shuffle")
+                        .action(ArgAction::SetTrue)
+                        .help("Shuffle the context's tracks"),
+                ),
+        )
+        .subcommand(Command::new("list").about("List the alarms"))
+        .subcommand(Command::new("clear").about("Remove all the alarms"))
+}
+
 pub fn init_like_command() -> Command {
     Command::new("like")
         .about("Like currently playing track")
@@ -159,6 +249,42 @@ pub fn init_generate_command() -> Command {
         )
 }
 
+pub fn init_cache_subcommand() -> Command {
+    Command::new("cache")
+        .about("Manage the application's cache")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("prune").about("Remove expired entries from the persisted lyrics cache"),
+        )
+        .subcommand(Command::new("usage").about("Show the disk usage of each persisted cache"))
+        .subcommand(
+            Command::new("clear").about("Clear persisted caches").arg(
+                Arg::new("caches")
+                    .value_parser(EnumValueParser::<CacheKind>::new())
+                    .action(ArgAction::Append)
+                    .num_args(1..)
+                    .required(true),
+            ),
+        )
+}
+
+#[cfg(feature = "streaming")]
+pub fn init_audio_devices_command() -> Command {
+    Command::new("audio-devices")
+        .about("List the output devices that the integrated player's audio backends can see")
+        .arg(
+            Arg::new("backend")
+                .short('b')
+                .long("backend")
+                .help("Only list the devices of the given audio backend")
+                .value_parser(clap::builder::PossibleValuesParser::new(
+                    librespot_playback::audio_backend::BACKENDS
+                        .iter()
+                        .map(|(name, _)| *name),
+                )),
+        )
+}
+
 pub fn init_playlist_subcommand() -> Command {
     Command::new("playlist")
         .about("Playlist editing")
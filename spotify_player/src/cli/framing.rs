@@ -0,0 +1,63 @@
+//! Framing of CLI socket responses.
+//!
+//! A response can be larger than a single UDP datagram, so it is split into chunks.
+//! Each chunk is prefixed with a header containing its sequence number and the total
+//! number of chunks. The receiver acknowledges received chunks by sending the number of
+//! contiguous chunks it has received so far, and the sender waits for an acknowledgement
+//! after each window of chunks, re-sending unacknowledged chunks on timeout.
+
+/// the maximum size of a chunk datagram, including its header
+const MAX_CHUNK_SIZE: usize = 4096;
+
+/// the size of a chunk's header: sequence number (u32) and total number of chunks (u32)
+pub const CHUNK_HEADER_SIZE: usize = 8;
+
+/// the maximum size of a chunk's payload
+pub const MAX_CHUNK_PAYLOAD_SIZE: usize = MAX_CHUNK_SIZE - CHUNK_HEADER_SIZE;
+
+/// the maximum size of a response, which bounds the number of chunks a receiver allocates
+pub const MAX_RESPONSE_SIZE: usize = 64 * 1024 * 1024;
+
+/// the maximum number of chunks of a response
+pub const MAX_CHUNKS: u32 = MAX_RESPONSE_SIZE.div_ceil(MAX_CHUNK_PAYLOAD_SIZE) as u32;
+
+/// the number of chunks sent before waiting for an acknowledgement
+pub const CHUNK_WINDOW_SIZE: u32 = 16;
+
+/// the duration to wait for chunks (or acknowledgements) before re-requesting (or re-sending) them
+pub const CHUNK_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// the maximum number of consecutive timeouts before a response is considered truncated
+pub const MAX_CHUNK_RETRIES: usize = 10;
+
+/// Encode a response's chunk with its sequence number and the total number of chunks
+pub fn encode_chunk(seq: u32, total: u32, payload: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(CHUNK_HEADER_SIZE + payload.len());
+    buf.extend_from_slice(&seq.to_be_bytes());
+    buf.extend_from_slice(&total.to_be_bytes());
+    buf.extend_from_slice(payload);
+    buf
+}
+
+/// Decode a response's chunk into its sequence number, the total number of chunks, and its payload
+pub fn decode_chunk(buf: &[u8]) -> Option<(u32, u32, &[u8])> {
+    if buf.len() < CHUNK_HEADER_SIZE {
+        return None;
+    }
+    let seq = u32::from_be_bytes(buf[0..4].try_into().ok()?);
+    let total = u32::from_be_bytes(buf[4..8].try_into().ok()?);
+    if seq >= total || total > MAX_CHUNKS {
+        return None;
+    }
+    Some((seq, total, &buf[CHUNK_HEADER_SIZE..]))
+}
+
+/// Decode an acknowledgement, which is the number of contiguous chunks received
+pub fn decode_ack(buf: &[u8]) -> Option<u32> {
+    Some(u32::from_be_bytes(buf.try_into().ok()?))
+}
+
+/// Check if the receiver should acknowledge after receiving the chunk `seq`
+pub fn should_ack(seq: u32, total: u32) -> bool {
+    (seq + 1).is_multiple_of(CHUNK_WINDOW_SIZE) || seq + 1 == total
+}
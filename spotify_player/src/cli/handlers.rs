@@ -1,31 +1,238 @@
-use crate::{auth::AuthConfig, client};
+use crate::{
+    auth::AuthConfig,
+    client,
+    state::{
+        audio_cache_file_path, load_data_from_file_cache, FileCacheKey, PinnedContext,
+        LYRICS_CACHE_FOLDER,
+    },
+};
 
+#[cfg(unix)]
+use super::unix_socket_path;
 use super::{
-    config, init_cli, start_socket, Command, ContextType, GetRequest, IdOrName, ItemType, Key,
-    PlaylistCommand, PlaylistId, Request, Response, MAX_REQUEST_SIZE,
+    config,
+    framing::{self, CHUNK_TIMEOUT, MAX_CHUNK_RETRIES},
+    init_cli, start_socket, AlarmCommand, CacheKind, Command, ContextType, GetRequest, IdOrName,
+    ItemType, Key, PlaylistCommand, PlaylistId, Request, Response, SleepCommand, SleepTimerMode,
+    MAX_REQUEST_SIZE,
 };
 use anyhow::{Context, Result};
-use clap::{ArgMatches, Id};
+use clap::{ArgMatches, Id, ValueEnum};
 use clap_complete::{generate, Shell};
+use std::collections::{HashMap, HashSet};
 use std::net::UdpSocket;
+use std::path::{Path, PathBuf};
 
 fn receive_response(socket: &UdpSocket) -> Result<Response> {
     // read response from the server's socket, which can be split into
     // smaller chunks of data
-    let mut data = Vec::new();
+    let mut chunks: Vec<Option<Vec<u8>>> = Vec::new();
+    // the number of contiguous chunks received from the start of the response
+    let mut received = 0;
+    let mut sender = None;
+    let mut retries = 0;
     let mut buf = [0; 4096];
     loop {
-        let (n_bytes, _) = socket.recv_from(&mut buf)?;
-        if n_bytes == 0 {
-            // end of chunk
-            break;
+        match socket.recv_from(&mut buf) {
+            Ok((n_bytes, addr)) => {
+                let Some((seq, total, payload)) = framing::decode_chunk(&buf[..n_bytes]) else {
+                    continue;
+                };
+                match sender {
+                    None => {
+                        // the response is being sent, so chunks are expected to arrive shortly
+                        socket.set_read_timeout(Some(CHUNK_TIMEOUT))?;
+                        chunks.resize(total as usize, None);
+                        sender = Some(addr);
+                    }
+                    // drop datagrams that don't belong to the response
+                    Some(sender) if sender != addr || total as usize != chunks.len() => continue,
+                    Some(_) => {}
+                }
+                retries = 0;
+
+                if let Some(chunk) = chunks.get_mut(seq as usize) {
+                    chunk.get_or_insert_with(|| payload.to_vec());
+                }
+                while chunks.get(received).is_some_and(Option::is_some) {
+                    received += 1;
+                }
+                if framing::should_ack(seq, total) || received == chunks.len() {
+                    socket.send_to(&(received as u32).to_be_bytes(), addr)?;
+                }
+                if received == chunks.len() {
+                    break;
+                }
+            }
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                retries += 1;
+                if retries > MAX_CHUNK_RETRIES {
+                    anyhow::bail!(
+                        "response is truncated: only {received} of {} chunks are received",
+                        chunks.len()
+                    );
+                }
+                // re-request the missing chunks
+                if let Some(addr) = sender {
+                    socket.send_to(&(received as u32).to_be_bytes(), addr)?;
+                }
+            }
+            Err(err) => return Err(err.into()),
         }
-        data.extend_from_slice(&buf[..n_bytes]);
     }
 
+    let data = chunks.into_iter().flatten().flatten().collect::<Vec<_>>();
     Ok(serde_json::from_slice(&data)?)
 }
 
+fn handle_cache_subcommand(args: &ArgMatches, configs: &config::Configs) -> Result<()> {
+    let (cmd, args) = args.subcommand().expect("cache subcommand is required");
+    match cmd {
+        "prune" => {
+            let removed = crate::state::prune_lyrics_file_cache(&configs.cache_folder)
+                .context("prune lyrics cache")?;
+            println!("Removed {removed} expired entries from the lyrics cache");
+        }
+        "usage" => {
+            for kind in CacheKind::value_variants() {
+                let files = get_cache_files(*kind, &configs.cache_folder)
+                    .with_context(|| format!("get files of the {kind:?} cache"))?;
+                let size = files.iter().map(|(_, size)| size).sum::<u64>();
+                print!(
+                    "{:<22}{:>12} ({} files)",
+                    kind.name(),
+                    format_size(size),
+                    files.len()
+                );
+                if let (CacheKind::Audio, Some(limit)) =
+                    (kind, configs.app_config.device.audio_cache_size_limit_mb)
+                {
+                    print!(", limited to {limit} MiB");
+                }
+                if kind
+                    .file_cache_key()
+                    .is_some_and(FileCacheKey::is_user_state)
+                {
+                    print!(", user state");
+                }
+                println!();
+            }
+        }
+        "clear" => {
+            let kinds = args
+                .get_many::<CacheKind>("caches")
+                .expect("caches is required")
+                .copied()
+                .collect::<Vec<_>>();
+            for kind in &kinds {
+                let mut files = get_cache_files(*kind, &configs.cache_folder)
+                    .with_context(|| format!("get files of the {kind:?} cache"))?;
+                // audio files of pinned contexts are needed to play the contexts offline,
+                // so they are kept unless the pinned contexts are cleared as well
+                let mut kept = 0;
+                if *kind == CacheKind::Audio && !kinds.contains(&CacheKind::PinnedContexts) {
+                    let pinned = get_pinned_audio_files(&configs.cache_folder)?;
+                    let n_files = files.len();
+                    files.retain(|(path, _)| !pinned.contains(path));
+                    kept = n_files - files.len();
+                }
+                for (path, _) in &files {
+                    std::fs::remove_file(path)
+                        .with_context(|| format!("remove {}", path.display()))?;
+                }
+                print!(
+                    "Removed {} files from the {} cache",
+                    files.len(),
+                    kind.name()
+                );
+                if kept > 0 {
+                    print!(", kept {kept} audio files of pinned contexts");
+                }
+                println!();
+            }
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Get the files (and their sizes in bytes) persisted in a cache
+fn get_cache_files(kind: CacheKind, cache_folder: &Path) -> Result<Vec<(PathBuf, u64)>> {
+    fn collect_files(folder: &Path, files: &mut Vec<(PathBuf, u64)>) -> std::io::Result<()> {
+        if !folder.exists() {
+            return Ok(());
+        }
+        for entry in std::fs::read_dir(folder)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                collect_files(&entry.path(), files)?;
+            } else {
+                files.push((entry.path(), metadata.len()));
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = vec![];
+    match kind {
+        CacheKind::Audio => collect_files(&cache_folder.join("audio"), &mut files)?,
+        CacheKind::Image => collect_files(&cache_folder.join("image"), &mut files)?,
+        CacheKind::Lyrics => collect_files(&cache_folder.join(LYRICS_CACHE_FOLDER), &mut files)?,
+        _ => {
+            let path = kind
+                .file_cache_key()
+                .expect("other caches should be file caches of user data")
+                .file_path(cache_folder);
+            if path.is_file() {
+                let size = std::fs::metadata(&path)?.len();
+                files.push((path, size));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Get the audio files of the contexts pinned for offline listening
+fn get_pinned_audio_files(cache_folder: &Path) -> Result<HashSet<PathBuf>> {
+    let key = FileCacheKey::PinnedContexts;
+    if !key.file_path(cache_folder).exists() {
+        return Ok(HashSet::new());
+    }
+    let pinned_contexts =
+        load_data_from_file_cache::<HashMap<String, PinnedContext>>(key, cache_folder)
+            .context("failed to load the pinned contexts")?;
+
+    let audio_folder = cache_folder.join("audio");
+    Ok(pinned_contexts
+        .values()
+        .flat_map(|c| c.tracks.iter())
+        .map(|t| audio_cache_file_path(&audio_folder, &t.file_id))
+        .collect())
+}
+
+/// Format a size in bytes into a human-readable string
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
 fn get_id_or_name(args: &ArgMatches) -> IdOrName {
     match args
         .get_one::<Id>("id_or_name")
@@ -138,12 +345,61 @@ fn handle_playback_subcommand(args: &ArgMatches) -> Result<Request> {
     Ok(Request::Playback(command))
 }
 
+fn handle_sleep_command(args: &ArgMatches) -> Request {
+    let fade_out_secs = args.get_one::<u64>("fade-out").copied();
+    let command = if let Some(minutes) = args.get_one::<u64>("minutes") {
+        SleepCommand::Set {
+            mode: SleepTimerMode::After { minutes: *minutes },
+            fade_out_secs,
+        }
+    } else if args.get_flag("end-of-track") {
+        SleepCommand::Set {
+            mode: SleepTimerMode::EndOfTrack,
+            fade_out_secs,
+        }
+    } else if args.get_flag("end-of-context") {
+        SleepCommand::Set {
+            mode: SleepTimerMode::EndOfContext,
+            fade_out_secs,
+        }
+    } else if args.get_flag("cancel") {
+        SleepCommand::Cancel
+    } else {
+        SleepCommand::Status
+    };
+
+    Request::Sleep(command)
+}
+
+fn handle_alarm_subcommand(args: &ArgMatches) -> Request {
+    let (cmd, args) = args.subcommand().expect("alarm subcommand is required");
+    let command = match cmd {
+        "add" => AlarmCommand::Add(config::Alarm {
+            time: args
+                .get_one::<String>("time")
+                .expect("time arg is required")
+                .to_owned(),
+            context_uri: args
+                .get_one::<String>("context_uri")
+                .expect("context_uri arg is required")
+                .to_owned(),
+            shuffle: args.get_flag("shuffle"),
+        }),
+        "list" => AlarmCommand::List,
+        "clear" => AlarmCommand::Clear,
+        _ => unreachable!(),
+    };
+
+    Request::Alarm(command)
+}
+
 /// Tries to connect to a running client, if exists, by sending a connection request
 /// to the client via a UDP socket.
 /// If no running client found, create a new client running in a separate thread to
 /// handle the socket request.
-fn try_connect_to_client(socket: &UdpSocket, configs: &config::Configs) -> Result<()> {
+fn try_connect_to_client(configs: &config::Configs) -> Result<()> {
     let port = configs.app_config.client_port;
+    let socket = UdpSocket::bind("127.0.0.1:0")?;
     socket.connect(("127.0.0.1", port))?;
 
     // send an empty buffer as a connection request to the client
@@ -174,6 +430,57 @@ fn try_connect_to_client(socket: &UdpSocket, configs: &config::Configs) -> Resul
     Ok(())
 }
 
+/// Send a request to a running client's socket, preferring the client's Unix socket
+/// (if available) over its UDP socket
+fn send_request(request: &Request, configs: &config::Configs) -> Result<Response> {
+    let request_buf = serde_json::to_vec(request)?;
+
+    #[cfg(unix)]
+    if let Ok(mut stream) = std::os::unix::net::UnixStream::connect(unix_socket_path()) {
+        use std::io::{Read, Write};
+
+        stream.write_all(&request_buf)?;
+        stream.shutdown(std::net::Shutdown::Write)?;
+        let mut response_buf = Vec::new();
+        stream.read_to_end(&mut response_buf)?;
+        return Ok(serde_json::from_slice(&response_buf)?);
+    }
+
+    try_connect_to_client(configs).context("try to connect to a client")?;
+    // the response is sent from a different address than the client's socket,
+    // so the request's socket should not be connected to the client's socket
+    let socket = UdpSocket::bind("127.0.0.1:0")?;
+
+    assert!(request_buf.len() <= MAX_REQUEST_SIZE);
+    socket.send_to(&request_buf, ("127.0.0.1", configs.app_config.client_port))?;
+    receive_response(&socket)
+}
+
+/// Subscribe to a running application's player state events via its Unix socket
+/// and print the events until the application stops
+#[cfg(unix)]
+fn handle_subscribe_command() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let path = unix_socket_path();
+    let mut stream = std::os::unix::net::UnixStream::connect(&path)
+        .with_context(|| format!("connect to a running application at {}", path.display()))?;
+    stream.write_all(&serde_json::to_vec(&Request::Subscribe)?)?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let mut stdout = std::io::stdout().lock();
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        // a failed subscription is responded with an error response instead of events
+        if let Ok(Response::Err(err)) = serde_json::from_str(&line) {
+            anyhow::bail!("{}", String::from_utf8_lossy(&err));
+        }
+        writeln!(stdout, "{line}")?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
 pub fn handle_cli_subcommand(cmd: &str, args: &ArgMatches) -> Result<()> {
     let configs = config::get_config();
 
@@ -193,12 +500,25 @@ pub fn handle_cli_subcommand(cmd: &str, args: &ArgMatches) -> Result<()> {
             generate(gen, &mut cmd, name, &mut std::io::stdout());
             std::process::exit(0);
         }
+        "cache" => {
+            handle_cache_subcommand(args, configs)?;
+            std::process::exit(0);
+        }
+        #[cfg(unix)]
+        "subscribe" => {
+            handle_subscribe_command()?;
+            std::process::exit(0);
+        }
+        #[cfg(feature = "streaming")]
+        "audio-devices" => {
+            crate::streaming::list_audio_devices(
+                args.get_one::<String>("backend").map(String::as_str),
+            )?;
+            std::process::exit(0);
+        }
         _ => {}
     }
 
-    let socket = UdpSocket::bind("127.0.0.1:0")?;
-    try_connect_to_client(&socket, configs).context("try to connect to a client")?;
-
     // construct a socket request based on the CLI command and its arguments
     let request = match cmd {
         "get" => handle_get_subcommand(args),
@@ -214,16 +534,22 @@ pub fn handle_cli_subcommand(cmd: &str, args: &ArgMatches) -> Result<()> {
                 .expect("query is required")
                 .to_owned(),
         },
+        "sleep" => handle_sleep_command(args),
+        "alarm" => handle_alarm_subcommand(args),
+        "exec" => {
+            let command = args
+                .get_many::<String>("command")
+                .expect("command is required")
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" ");
+            Request::Exec(command.parse()?)
+        }
         _ => unreachable!(),
     };
 
-    // send the request to the client's socket
-    let request_buf = serde_json::to_vec(&request)?;
-    assert!(request_buf.len() <= MAX_REQUEST_SIZE);
-    socket.send(&request_buf)?;
-
-    // receive and handle a response from the client's socket
-    match receive_response(&socket)? {
+    // send the request to the client's socket and handle its response
+    match send_request(&request, configs)? {
         Response::Err(err) => {
             eprintln!("{}", String::from_utf8_lossy(&err));
             std::process::exit(1);
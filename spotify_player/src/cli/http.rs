@@ -0,0 +1,345 @@
+//! A local HTTP/JSON API exposing the same operations as the CLI socket.
+//!
+//! Every endpoint is translated into a [`Request`], which is then handled by
+//! the same handler as the socket requests. The `/events` endpoint additionally
+//! streams the player's state events to subscribers.
+//!
+//! As the API controls the user's Spotify account, it must not be reachable from
+//! web pages opened in a browser, see [`check_request`].
+
+use anyhow::Result;
+use axum::{
+    body::Body,
+    extract::{Path, Query, Request as HttpRequest, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post, put},
+    Json, Router,
+};
+use clap::ValueEnum;
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use tracing::Instrument;
+
+use crate::{client::Client, config, state::SharedState};
+
+use super::{
+    client::handle_socket_request, Command, Deserialize, GetRequest, IdOrName, ItemType, Key,
+    PlaylistCommand, Request,
+};
+
+#[derive(Clone)]
+struct ApiState {
+    client: Client,
+    state: Option<SharedState>,
+    /// the bearer token required to use the API
+    token: Option<String>,
+    /// the allowed values of requests' `Host` header
+    hosts: Vec<String>,
+}
+
+/// Query parameters identifying a Spotify item either by its id or by its name
+#[derive(Debug, Deserialize)]
+struct IdOrNameQuery {
+    id: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    query: String,
+}
+
+/// Start a HTTP server handling API requests at the given `address`
+pub async fn start_http_server(
+    client: Client,
+    address: &str,
+    state: Option<SharedState>,
+) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    let local_addr = listener.local_addr()?;
+    tracing::info!("Starting a HTTP API server at {local_addr}");
+
+    let mut hosts = vec![address.to_string(), local_addr.to_string()];
+    if local_addr.ip().is_loopback() {
+        hosts.push(format!("localhost:{}", local_addr.port()));
+    }
+    let state = ApiState {
+        client,
+        state,
+        token: config::get_config().app_config.http_api_token.clone(),
+        hosts,
+    };
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/playback", get(get_playback).post(post_playback))
+        .route("/playback/{command}", post(post_playback_command))
+        .route("/queue", get(|s| get_key(s, Key::Queue)))
+        .route("/devices", get(|s| get_key(s, Key::Devices)))
+        .route("/liked-tracks", get(|s| get_key(s, Key::UserLikedTracks)))
+        .route("/top-tracks", get(|s| get_key(s, Key::UserTopTracks)))
+        .route("/saved-albums", get(|s| get_key(s, Key::UserSavedAlbums)))
+        .route(
+            "/followed-artists",
+            get(|s| get_key(s, Key::UserFollowedArtists)),
+        )
+        .route(
+            "/playlists",
+            get(|s| get_key(s, Key::UserPlaylists)).post(post_playlist),
+        )
+        .route("/items/{item_type}", get(get_item))
+        .route("/search", get(search))
+        .route("/connect", post(connect))
+        .route("/like", put(like).delete(unlike))
+        .route("/request", post(raw_request))
+        .route("/events", get(events))
+        .layer(middleware::from_fn_with_state(state.clone(), check_request))
+        .with_state(state)
+}
+
+/// Reject requests that are not allowed to use the API.
+///
+/// If `http_api_token` is configured, a request must have the token as its bearer token.
+/// Otherwise, a request must be addressed to the server's address (to prevent DNS rebinding)
+/// and must not have an `Origin` header, which browsers add to cross-origin requests.
+/// Requests to mutating routes must also have a JSON content type, which browsers
+/// cannot send cross-origin without a CORS preflight request.
+async fn check_request(
+    State(state): State<ApiState>,
+    request: HttpRequest,
+    next: Next,
+) -> Response {
+    let headers = request.headers();
+    let header_value = |name| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if let Some(token) = &state.token {
+        let is_authorized = header_value(header::AUTHORIZATION)
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|t| is_same_token(t, token));
+        if !is_authorized {
+            return (
+                StatusCode::UNAUTHORIZED,
+                "Unauthorized: invalid bearer token",
+            )
+                .into_response();
+        }
+    } else {
+        let is_allowed_host =
+            header_value(header::HOST).is_some_and(|host| state.hosts.iter().any(|h| h == host));
+        if !is_allowed_host || headers.contains_key(header::ORIGIN) {
+            return (
+                StatusCode::FORBIDDEN,
+                "Forbidden: requests from web browsers are not allowed",
+            )
+                .into_response();
+        }
+    }
+
+    let is_mutating = !matches!(*request.method(), Method::GET | Method::HEAD);
+    let is_json = header_value(header::CONTENT_TYPE)
+        .is_some_and(|v| v.split(';').next() == Some("application/json"));
+    if is_mutating && !is_json {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Unsupported media type: expected `Content-Type: application/json`",
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Compare a token with the expected token in a constant time
+fn is_same_token(token: &str, expected: &str) -> bool {
+    token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// The status code of a failed request's response: failures of the Spotify API,
+/// the Spotify session or the application itself are server errors, others are
+/// caused by the request
+fn error_status(err: &anyhow::Error) -> StatusCode {
+    if err
+        .chain()
+        .any(|e| e.is::<rspotify::ClientError>() || e.is::<librespot_core::Error>())
+    {
+        StatusCode::BAD_GATEWAY
+    } else if err
+        .chain()
+        .any(|e| e.is::<std::io::Error>() || e.is::<serde_json::Error>())
+    {
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Handle a request using the CLI socket request handler
+async fn handle_request(state: &ApiState, request: Request) -> Response {
+    // responses of `Get` and `Search` requests are JSON data, others are plain text
+    let is_json = matches!(request, Request::Get(_) | Request::Search { .. });
+    let span = tracing::info_span!("http_request", request = ?request);
+
+    async {
+        match handle_socket_request(&state.client, state.state.as_ref(), request).await {
+            Err(err) => {
+                tracing::error!("Failed to handle HTTP request: {err:#}");
+                let status = error_status(&err);
+                let reason = status.canonical_reason().unwrap_or_default();
+                (status, format!("{reason}: {err:#}")).into_response()
+            }
+            Ok(data) => {
+                tracing::info!("Successfully handled the HTTP request.");
+                if data.is_empty() {
+                    StatusCode::NO_CONTENT.into_response()
+                } else {
+                    let content_type = if is_json {
+                        "application/json"
+                    } else {
+                        "text/plain; charset=utf-8"
+                    };
+                    ([(header::CONTENT_TYPE, content_type)], data).into_response()
+                }
+            }
+        }
+    }
+    .instrument(span)
+    .await
+}
+
+fn bad_request(msg: String) -> Response {
+    (StatusCode::BAD_REQUEST, msg).into_response()
+}
+
+fn to_id_or_name(query: IdOrNameQuery) -> Option<IdOrName> {
+    match query {
+        IdOrNameQuery { id: Some(id), .. } => Some(IdOrName::Id(id)),
+        IdOrNameQuery {
+            name: Some(name), ..
+        } => Some(IdOrName::Name(name)),
+        _ => None,
+    }
+}
+
+async fn get_key(State(state): State<ApiState>, key: Key) -> Response {
+    handle_request(&state, Request::Get(GetRequest::Key(key))).await
+}
+
+async fn get_playback(state: State<ApiState>) -> Response {
+    get_key(state, Key::Playback).await
+}
+
+async fn post_playback(State(state): State<ApiState>, Json(command): Json<Command>) -> Response {
+    handle_request(&state, Request::Playback(command)).await
+}
+
+/// Handle a playback command that doesn't require any argument
+async fn post_playback_command(
+    State(state): State<ApiState>,
+    Path(command): Path<String>,
+) -> Response {
+    let command = match command.as_str() {
+        "play-pause" => Command::PlayPause,
+        "play" => Command::Play,
+        "pause" => Command::Pause,
+        "next" => Command::Next,
+        "previous" => Command::Previous,
+        "shuffle" => Command::Shuffle,
+        "repeat" => Command::Repeat,
+        _ => return bad_request(format!("Bad request: unknown playback command {command}")),
+    };
+    handle_request(&state, Request::Playback(command)).await
+}
+
+async fn post_playlist(
+    State(state): State<ApiState>,
+    Json(command): Json<PlaylistCommand>,
+) -> Response {
+    handle_request(&state, Request::Playlist(command)).await
+}
+
+async fn get_item(
+    State(state): State<ApiState>,
+    Path(item_type): Path<String>,
+    Query(query): Query<IdOrNameQuery>,
+) -> Response {
+    let Ok(item_type) = ItemType::from_str(&item_type, true) else {
+        return bad_request(format!("Bad request: unknown item type {item_type}"));
+    };
+    let Some(id_or_name) = to_id_or_name(query) else {
+        return bad_request("Bad request: either `id` or `name` is required".to_string());
+    };
+    handle_request(
+        &state,
+        Request::Get(GetRequest::Item(item_type, id_or_name)),
+    )
+    .await
+}
+
+async fn search(State(state): State<ApiState>, Query(query): Query<SearchQuery>) -> Response {
+    handle_request(&state, Request::Search { query: query.query }).await
+}
+
+async fn connect(State(state): State<ApiState>, Query(query): Query<IdOrNameQuery>) -> Response {
+    let Some(id_or_name) = to_id_or_name(query) else {
+        return bad_request("Bad request: either `id` or `name` is required".to_string());
+    };
+    handle_request(&state, Request::Connect(id_or_name)).await
+}
+
+async fn like(State(state): State<ApiState>) -> Response {
+    handle_request(&state, Request::Like { unlike: false }).await
+}
+
+async fn unlike(State(state): State<ApiState>) -> Response {
+    handle_request(&state, Request::Like { unlike: true }).await
+}
+
+async fn raw_request(State(state): State<ApiState>, Json(request): Json<Request>) -> Response {
+    handle_request(&state, request).await
+}
+
+/// Stream the player's state events, as server-sent events if requested by
+/// the `Accept` header or as newline-delimited JSON otherwise
+async fn events(State(state): State<ApiState>, headers: HeaderMap) -> Response {
+    let Some(app_state) = state.state.as_ref() else {
+        return bad_request("Bad request: no running application to subscribe to".to_string());
+    };
+    tracing::info!("New subscriber to the player's state events");
+
+    // events missed by a lagging subscriber are skipped
+    let events = BroadcastStream::new(app_state.events.subscribe()).filter_map(Result::ok);
+
+    let is_sse = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/event-stream"));
+    if is_sse {
+        Sse::new(events.map(|event| Event::default().json_data(event)))
+            .keep_alive(KeepAlive::default())
+            .into_response()
+    } else {
+        let lines = events.map(|event| {
+            serde_json::to_vec(&event).map(|mut line| {
+                line.push(b'\n');
+                line
+            })
+        });
+        (
+            [(header::CONTENT_TYPE, "application/x-ndjson")],
+            Body::from_stream(lines),
+        )
+            .into_response()
+    }
+}
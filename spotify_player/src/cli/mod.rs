@@ -1,8 +1,15 @@
 mod client;
 mod commands;
+mod framing;
 mod handlers;
+mod http;
+#[cfg(unix)]
+mod unix_socket;
 
 use crate::config;
+use crate::ex_command::ExCommand;
+use crate::state::{FileCacheKey, SleepTimerMode};
+use clap::ValueEnum;
 use rspotify::model::{AlbumId, ArtistId, Id, PlaylistId, TrackId};
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +17,9 @@ const MAX_REQUEST_SIZE: usize = 4096;
 
 pub use client::start_socket;
 pub use handlers::handle_cli_subcommand;
+pub use http::start_http_server;
+#[cfg(unix)]
+pub use unix_socket::{start_unix_socket, unix_socket_path};
 
 #[derive(Debug, Serialize, Deserialize, clap::ValueEnum, Clone)]
 pub enum Key {
@@ -38,6 +48,35 @@ pub enum ItemType {
     Track,
 }
 
+/// A persisted cache of the application
+#[derive(Debug, clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    /// audio files of the integrated player
+    Audio,
+    /// cover images
+    Image,
+    /// tracks' lyrics
+    Lyrics,
+    /// user's playlists
+    Playlists,
+    /// user's playlist folders
+    PlaylistFolders,
+    /// user's followed artists
+    FollowedArtists,
+    /// user's saved shows
+    SavedShows,
+    /// user's saved albums
+    SavedAlbums,
+    /// user's liked tracks
+    SavedTracks,
+    /// user-adjusted timing offsets of tracks' lyrics (user state, can't be fetched again)
+    LyricsOffsets,
+    /// contexts pinned for offline listening (user state, can't be fetched again)
+    PinnedContexts,
+    /// history of the command line (user state, can't be fetched again)
+    CommandLineHistory,
+}
+
 /// Spotify item's ID
 enum ItemId {
     Playlist(PlaylistId<'static>),
@@ -84,6 +123,23 @@ pub enum PlaylistCommand {
     },
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SleepCommand {
+    Set {
+        mode: SleepTimerMode,
+        fade_out_secs: Option<u64>,
+    },
+    Cancel,
+    Status,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum AlarmCommand {
+    Add(config::Alarm),
+    List,
+    Clear,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Command {
     StartContext {
@@ -109,6 +165,8 @@ pub enum Command {
         is_offset: bool,
     },
     Seek(i64),
+    /// seek to an absolute position (in milliseconds)
+    SeekTo(i64),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -116,9 +174,19 @@ pub enum Request {
     Get(GetRequest),
     Playback(Command),
     Connect(IdOrName),
-    Like { unlike: bool },
+    Like {
+        unlike: bool,
+    },
     Playlist(PlaylistCommand),
-    Search { query: String },
+    Search {
+        query: String,
+    },
+    Sleep(SleepCommand),
+    Alarm(AlarmCommand),
+    Exec(ExCommand),
+    /// Subscribe to the player's state events, which are streamed as newline-delimited JSON.
+    /// Only supported by the Unix socket.
+    Subscribe,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -137,6 +205,31 @@ impl From<ContextType> for ItemType {
     }
 }
 
+impl CacheKind {
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .expect("no skipped variant")
+            .get_name()
+            .to_string()
+    }
+
+    /// Get the key of the cache if it is a file cache of user data
+    pub fn file_cache_key(self) -> Option<FileCacheKey> {
+        match self {
+            Self::Audio | Self::Image | Self::Lyrics => None,
+            Self::Playlists => Some(FileCacheKey::Playlists),
+            Self::PlaylistFolders => Some(FileCacheKey::PlaylistFolders),
+            Self::FollowedArtists => Some(FileCacheKey::FollowedArtists),
+            Self::SavedShows => Some(FileCacheKey::SavedShows),
+            Self::SavedAlbums => Some(FileCacheKey::SavedAlbums),
+            Self::SavedTracks => Some(FileCacheKey::SavedTracks),
+            Self::LyricsOffsets => Some(FileCacheKey::LyricsOffsets),
+            Self::PinnedContexts => Some(FileCacheKey::PinnedContexts),
+            Self::CommandLineHistory => Some(FileCacheKey::CommandLineHistory),
+        }
+    }
+}
+
 impl ItemId {
     pub fn uri(&self) -> String {
         match self {
@@ -163,7 +256,11 @@ pub fn init_cli() -> anyhow::Result<clap::Command> {
         .subcommand(commands::init_authenticate_command())
         .subcommand(commands::init_playlist_subcommand())
         .subcommand(commands::init_generate_command())
+        .subcommand(commands::init_cache_subcommand())
         .subcommand(commands::init_search_command())
+        .subcommand(commands::init_sleep_command())
+        .subcommand(commands::init_alarm_subcommand())
+        .subcommand(commands::init_exec_command())
         .arg(
             clap::Arg::new("theme")
                 .short('t')
@@ -197,5 +294,11 @@ pub fn init_cli() -> anyhow::Result<clap::Command> {
             .help("Running the application as a daemon"),
     );
 
+    #[cfg(feature = "streaming")]
+    let cmd = cmd.subcommand(commands::init_audio_devices_command());
+
+    #[cfg(unix)]
+    let cmd = cmd.subcommand(commands::init_subscribe_command());
+
     Ok(cmd)
 }
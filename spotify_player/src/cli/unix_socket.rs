@@ -0,0 +1,142 @@
+//! A Unix domain socket for handling CLI requests.
+//!
+//! Unlike the UDP client socket, the Unix socket is only accessible by the current user.
+//! A request is sent as a JSON [`Request`] followed by the end of the stream's write half,
+//! and its JSON [`Response`] is sent back before the stream is closed.
+//! A [`Request::Subscribe`] request is instead responded with the player's state events,
+//! one JSON event per line, until the subscriber closes the stream.
+
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast;
+use tracing::Instrument;
+
+use crate::{client::Client, config, state::SharedState};
+
+use super::{client::handle_socket_request, Request, Response};
+
+/// Get the path of the application's Unix socket, which is named after the client's port
+/// (`client_port`) so that applications with different ports have different sockets.
+///
+/// The socket is located in a `spotify-player` folder of the user's runtime directory
+/// (`$XDG_RUNTIME_DIR`), or in a `sockets` folder of the application's cache folder
+/// if there is no runtime directory.
+pub fn unix_socket_path() -> PathBuf {
+    let configs = config::get_config();
+    let folder = match dirs_next::runtime_dir() {
+        Some(dir) => dir.join("spotify-player"),
+        None => configs.cache_folder.join("sockets"),
+    };
+    folder.join(format!("client-{}.sock", configs.app_config.client_port))
+}
+
+/// Start a Unix socket handling CLI requests at the given `path`
+pub async fn start_unix_socket(
+    client: Client,
+    path: &Path,
+    state: Option<SharedState>,
+) -> Result<()> {
+    // the socket is only accessible by the current user as its folder is,
+    // which is set before the socket is created
+    if let Some(folder) = path.parent() {
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(folder)
+            .with_context(|| format!("create {}", folder.display()))?;
+        std::fs::set_permissions(folder, std::fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("restrict the access to {}", folder.display()))?;
+    }
+
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            anyhow::bail!("another application is listening on {}", path.display());
+        }
+        // remove the stale socket of a previous application
+        std::fs::remove_file(path).context("remove stale socket")?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    tracing::info!("Starting a client Unix socket at {}", path.display());
+
+    loop {
+        match listener.accept().await {
+            Err(err) => tracing::warn!("Failed to accept a Unix socket connection: {err:#}"),
+            Ok((stream, _)) => {
+                let client = client.clone();
+                let state = state.clone();
+                tokio::task::spawn(async move {
+                    if let Err(err) = handle_connection(&client, state.as_ref(), stream).await {
+                        tracing::error!("Failed to handle Unix socket connection: {err:#}");
+                    }
+                });
+            }
+        }
+    }
+}
+
+async fn handle_connection(
+    client: &Client,
+    state: Option<&SharedState>,
+    mut stream: UnixStream,
+) -> Result<()> {
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).await?;
+    let request: Request =
+        serde_json::from_slice(&buf).context("deserialize the socket request")?;
+    if let Request::Subscribe = request {
+        return stream_events(state, stream).await;
+    }
+
+    let span = tracing::info_span!("unix_socket_request", request = ?request);
+    async {
+        let response = match handle_socket_request(client, state, request).await {
+            Err(err) => {
+                tracing::error!("Failed to handle socket request: {err:#}");
+                let msg = format!("Bad request: {err:#}");
+                Response::Err(msg.into_bytes())
+            }
+            Ok(data) => Response::Ok(data),
+        };
+        stream.write_all(&serde_json::to_vec(&response)?).await?;
+        stream.shutdown().await?;
+
+        tracing::info!("Successfully handled the socket request.");
+        Ok(())
+    }
+    .instrument(span)
+    .await
+}
+
+/// Stream the player's state events to a subscriber as newline-delimited JSON
+async fn stream_events(state: Option<&SharedState>, mut stream: UnixStream) -> Result<()> {
+    let Some(state) = state else {
+        let msg = "Bad request: no running application to subscribe to";
+        let response = Response::Err(msg.as_bytes().to_vec());
+        stream.write_all(&serde_json::to_vec(&response)?).await?;
+        return Ok(());
+    };
+    tracing::info!("New Unix socket subscriber to the player's state events");
+
+    let mut events = state.events.subscribe();
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!("A Unix socket subscriber skipped {skipped} player state events");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        };
+        let mut line = serde_json::to_vec(&event)?;
+        line.push(b'\n');
+        if stream.write_all(&line).await.is_err() {
+            tracing::info!("A Unix socket subscriber has disconnected");
+            return Ok(());
+        }
+    }
+}
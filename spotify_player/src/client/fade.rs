@@ -0,0 +1,244 @@
+//! Volume fades applied when pausing, resuming, skipping and transferring the playback.
+//!
+//! A fade ramps the playback's volume in small steps, via volume requests for remote devices
+//! and directly via the mixer for the integrated device. The original volume is restored
+//! once the faded player action is done, or once the fade is interrupted by another player request.
+//! An interrupted fade stops ramping the volume, and its action runs right away.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use anyhow::Result;
+use rspotify::prelude::OAuthClient;
+
+use crate::config;
+use crate::state::PlaybackMetadata;
+
+use super::Client;
+
+/// the interval between two volume steps of a fade on a remote device
+const REMOTE_FADE_STEP: Duration = Duration::from_millis(200);
+/// the interval between two volume steps of a fade on the integrated device
+#[cfg(feature = "streaming")]
+const INTEGRATED_FADE_STEP: Duration = Duration::from_millis(10);
+
+static NEXT_FADE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A volume fade in progress
+#[derive(Debug)]
+pub struct VolumeFade {
+    id: u64,
+    target: FadeTarget,
+}
+
+/// The device whose volume is faded, with its original volume
+#[derive(Debug, Clone)]
+enum FadeTarget {
+    Remote {
+        device_id: Option<String>,
+        volume: u8,
+    },
+    #[cfg(feature = "streaming")]
+    Integrated { volume: u16 },
+}
+
+/// The status of a volume ramp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RampStatus {
+    Done,
+    /// the ramp stopped because its fade is interrupted
+    Interrupted,
+}
+
+impl FadeTarget {
+    fn step(&self) -> Duration {
+        match self {
+            Self::Remote { .. } => REMOTE_FADE_STEP,
+            #[cfg(feature = "streaming")]
+            Self::Integrated { .. } => INTEGRATED_FADE_STEP,
+        }
+    }
+}
+
+impl Client {
+    /// Run a player action on the playback, fading out the playback's volume before the action
+    /// and (optionally) fading it in after the action. The original volume is restored afterwards.
+    ///
+    /// Fades are disabled if both `volume_fade_out_ms` and `volume_fade_in_ms` are zero.
+    pub(super) async fn with_volume_fade<T, E>(
+        &self,
+        playback: &PlaybackMetadata,
+        fade_out: bool,
+        fade_in: bool,
+        action: impl Future<Output = Result<T, E>>,
+    ) -> Result<T>
+    where
+        anyhow::Error: From<E>,
+    {
+        let configs = config::get_config();
+        let duration = |enabled: bool, ms: u64| {
+            if enabled {
+                Duration::from_millis(ms)
+            } else {
+                Duration::ZERO
+            }
+        };
+        let fade_out = duration(fade_out, configs.app_config.volume_fade_out_ms);
+        let fade_in = duration(fade_in, configs.app_config.volume_fade_in_ms);
+        if fade_out.is_zero() && fade_in.is_zero() {
+            return Ok(action.await?);
+        }
+        let Some(id) = self.start_volume_fade(playback) else {
+            return Ok(action.await?);
+        };
+
+        // a zero-duration fade out silences the playback before the action,
+        // so that the action's audio can be faded in
+        let status = match self.ramp_volume(id, 1.0, 0.0, fade_out).await {
+            Ok(status) => status,
+            Err(err) => {
+                self.finish_volume_fade(id).await?;
+                return Err(err);
+            }
+        };
+        if status == RampStatus::Interrupted {
+            // the interrupting request has restored the original volume
+            tracing::info!(
+                "The volume fade out is interrupted, running the player action without fading"
+            );
+            return Ok(action.await?);
+        }
+
+        let result = action.await.map_err(anyhow::Error::from);
+        if result.is_ok() && !fade_in.is_zero() {
+            if let Err(err) = self.ramp_volume(id, 0.0, 1.0, fade_in).await {
+                tracing::warn!("Failed to fade in the playback's volume: {err:#}");
+            }
+        }
+        self.finish_volume_fade(id).await?;
+        result
+    }
+
+    /// Interrupt the volume fade in progress (if any), restoring the original volume
+    pub(super) async fn interrupt_volume_fade(&self) -> Result<()> {
+        let _write = self.volume_fade_write.lock().await;
+        let fade = self.volume_fade.lock().take();
+        if let Some(fade) = fade {
+            tracing::info!("Interrupted a volume fade, restoring the original volume");
+            self.set_fade_volume(&fade.target, 1.0).await?;
+        }
+        Ok(())
+    }
+
+    /// Start a volume fade on the playback's device, returning the fade's id.
+    /// Return `None` if the playback's volume can't be faded.
+    fn start_volume_fade(&self, playback: &PlaybackMetadata) -> Option<u64> {
+        if playback.mute_state.is_some() {
+            return None;
+        }
+
+        let mut fade = self.volume_fade.lock();
+        let target = match fade.take() {
+            // the original volume of a replaced fade is kept
+            Some(fade) => fade.target,
+            None => self.fade_target(playback)?,
+        };
+        let id = NEXT_FADE_ID.fetch_add(1, Ordering::Relaxed);
+        *fade = Some(VolumeFade { id, target });
+        Some(id)
+    }
+
+    // the client's mixer is only used with the `streaming` feature
+    #[cfg_attr(not(feature = "streaming"), allow(clippy::unused_self))]
+    fn fade_target(&self, playback: &PlaybackMetadata) -> Option<FadeTarget> {
+        #[cfg(feature = "streaming")]
+        if playback.device_name == config::get_config().app_config.device.name {
+            if let Some(mixer) = self.mixer.lock().as_ref() {
+                return Some(FadeTarget::Integrated {
+                    volume: mixer.volume(),
+                });
+            }
+        }
+
+        Some(FadeTarget::Remote {
+            device_id: playback.device_id.clone(),
+            volume: u8::try_from(playback.volume?).ok()?,
+        })
+    }
+
+    /// Ramp the volume of a fade between two fractions of the original volume during `duration`.
+    /// The ramp stops once the fade is interrupted.
+    async fn ramp_volume(
+        &self,
+        id: u64,
+        from: f64,
+        to: f64,
+        duration: Duration,
+    ) -> Result<RampStatus> {
+        let Some(target) = self.fade_target_of(id) else {
+            return Ok(RampStatus::Interrupted);
+        };
+        let n_steps = (duration.as_secs_f64() / target.step().as_secs_f64()).ceil() as u32;
+        let n_steps = n_steps.max(1);
+
+        for i in 1..=n_steps {
+            {
+                // check if the fade is interrupted before each step, the fade can't be
+                // interrupted until the step's volume is written
+                let _write = self.volume_fade_write.lock().await;
+                let Some(target) = self.fade_target_of(id) else {
+                    return Ok(RampStatus::Interrupted);
+                };
+                let fraction = from + (to - from) * f64::from(i) / f64::from(n_steps);
+                self.set_fade_volume(&target, fraction).await?;
+            }
+            if i < n_steps {
+                tokio::time::sleep(duration / n_steps).await;
+            }
+        }
+        Ok(RampStatus::Done)
+    }
+
+    /// Finish a volume fade by restoring the original volume, unless the fade is interrupted
+    async fn finish_volume_fade(&self, id: u64) -> Result<()> {
+        let _write = self.volume_fade_write.lock().await;
+        let fade = {
+            let mut fade = self.volume_fade.lock();
+            if fade.as_ref().is_some_and(|f| f.id == id) {
+                fade.take()
+            } else {
+                None
+            }
+        };
+        if let Some(fade) = fade {
+            self.set_fade_volume(&fade.target, 1.0).await?;
+        }
+        Ok(())
+    }
+
+    fn fade_target_of(&self, id: u64) -> Option<FadeTarget> {
+        self.volume_fade
+            .lock()
+            .as_ref()
+            .filter(|f| f.id == id)
+            .map(|f| f.target.clone())
+    }
+
+    /// Set the volume of a fade's device to a fraction of its original volume
+    async fn set_fade_volume(&self, target: &FadeTarget, fraction: f64) -> Result<()> {
+        match target {
+            FadeTarget::Remote { device_id, volume } => {
+                let volume = (f64::from(*volume) * fraction).round() as u8;
+                self.volume(volume, device_id.as_deref()).await?;
+            }
+            #[cfg(feature = "streaming")]
+            FadeTarget::Integrated { volume } => {
+                if let Some(mixer) = self.mixer.lock().as_ref() {
+                    mixer.set_volume((f64::from(*volume) * fraction).round() as u16);
+                }
+            }
+        }
+        Ok(())
+    }
+}
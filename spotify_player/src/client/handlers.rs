@@ -4,11 +4,11 @@ use tracing::Instrument;
 
 use crate::{
     config,
-    state::{ContextId, ContextPageType, ContextPageUIState, PageState, PlayableId, SharedState},
+    state::{
+        ContextId, ContextPageType, ContextPageUIState, PageState, PlayableId, SharedState, Track,
+    },
 };
 
-use crate::utils::map_join;
-
 use super::ClientRequest;
 
 struct PlayerEventHandlerState {
@@ -24,6 +24,16 @@ pub async fn start_client_handler(
 ) {
     while let Ok(request) = client_sub.recv_async().await {
         if let Err(err) = client.check_valid_session(&state).await {
+            // without a connection to Spotify, pinned contexts are still available
+            #[cfg(feature = "streaming")]
+            match client.handle_offline_request(&state, &request) {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(err) => {
+                    tracing::error!("Failed to handle client request offline: {err:#}");
+                    continue;
+                }
+            }
             tracing::error!("{err:#}");
             continue;
         }
@@ -171,18 +181,22 @@ fn handle_page_change_event(
             track_uri,
             track,
             artists,
+            cursor,
+            scroll_offset,
         } => {
             if let Some(rspotify::model::PlayableItem::Track(current_track)) =
                 state.player.read().currently_playing()
             {
                 if current_track.name != *track {
-                    if let Some(id) = &current_track.id {
+                    if let Some(current_track) = Track::try_from_full_track(current_track.clone()) {
                         tracing::info!("Currently playing track \"{}\" is different from the track \"{track}\" shown up in the lyrics page. Fetching new track's lyrics...", current_track.name);
                         track.clone_from(&current_track.name);
-                        *artists = map_join(&current_track.artists, |a| &a.name, ", ");
-                        *track_uri = id.uri();
+                        *artists = current_track.artists_info();
+                        *track_uri = current_track.id.uri();
+                        cursor.select(None);
+                        *scroll_offset = 0;
                         client_pub.send(ClientRequest::GetLyrics {
-                            track_id: id.clone_static(),
+                            track: current_track,
                         })?;
                     }
                 }
@@ -203,6 +217,7 @@ fn handle_player_event(
         .context("handle page change event")?;
     handle_playback_change_event(state, client_pub, handler_state)
         .context("handle playback change event")?;
+    state.publish_player_state_changes(false);
 
     Ok(())
 }
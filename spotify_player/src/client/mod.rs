@@ -2,15 +2,18 @@ use std::ops::Deref;
 use std::{borrow::Cow, collections::HashMap, sync::Arc};
 
 use crate::state::Lyrics;
-use crate::{auth, config};
+use crate::{
+    auth,
+    config::{self, LyricsProvider},
+};
 use crate::{
     auth::AuthConfig,
     state::{
-        store_data_into_file_cache, Album, AlbumId, Artist, ArtistId, Category, Context, ContextId,
-        Device, FileCacheKey, Item, ItemId, MemoryCaches, Playback, PlaybackMetadata, Playlist,
-        PlaylistFolderItem, PlaylistId, SearchResults, SharedState, Show, ShowId, Track, TrackId,
-        UserId, TTL_CACHE_DURATION, USER_LIKED_TRACKS_ID, USER_RECENTLY_PLAYED_TRACKS_ID,
-        USER_TOP_TRACKS_ID,
+        load_lyrics_from_file_cache, store_data_into_file_cache, store_lyrics_into_file_cache,
+        Album, AlbumId, Artist, ArtistId, Category, Context, ContextId, Device, FileCacheKey, Item,
+        ItemId, MemoryCaches, Playback, PlaybackMetadata, Playlist, PlaylistFolderItem, PlaylistId,
+        SearchResults, SharedState, Show, ShowId, Track, TrackId, UserId, TTL_CACHE_DURATION,
+        USER_LIKED_TRACKS_ID, USER_RECENTLY_PLAYED_TRACKS_ID, USER_TOP_TRACKS_ID,
     },
 };
 
@@ -19,21 +22,35 @@ use std::io::Write;
 use anyhow::Context as _;
 use anyhow::Result;
 
-#[cfg(feature = "streaming")]
 use parking_lot::Mutex;
 
 use reqwest::StatusCode;
 use rspotify::{http::Query, prelude::*};
 
+mod fade;
 mod handlers;
+#[cfg(feature = "streaming")]
+mod offline;
+#[cfg(feature = "streaming")]
+mod offline_player;
 mod request;
+mod scheduler;
 mod spotify;
 
 pub use handlers::*;
+#[cfg(feature = "streaming")]
+pub use offline::prune_audio_cache;
 pub use request::*;
+pub use scheduler::start_scheduler;
 use serde::Deserialize;
 
 const SPOTIFY_API_ENDPOINT: &str = "https://api.spotify.com/v1";
+/// the maximum number of items in a request to the playlist items endpoints
+const PLAYLIST_ITEMS_LIMIT: usize = 100;
+/// the maximum number of tracks in a request to the liked tracks endpoints
+const SAVED_TRACKS_LIMIT: usize = 50;
+/// the maximum duration of the `lyrics_translation_command`
+const LYRICS_TRANSLATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 const PLAYBACK_TYPES: [&rspotify::model::AdditionalType; 2] = [
     &rspotify::model::AdditionalType::Track,
     &rspotify::model::AdditionalType::Episode,
@@ -47,6 +64,16 @@ pub struct Client {
     auth_config: AuthConfig,
     #[cfg(feature = "streaming")]
     stream_conn: Arc<Mutex<Option<librespot_connect::spirc::Spirc>>>,
+    /// the integrated player's mixer, which is used to fade the integrated device's volume
+    #[cfg(feature = "streaming")]
+    mixer: Arc<Mutex<Option<Arc<dyn librespot_playback::mixer::Mixer>>>>,
+    /// the player of pinned tracks when there is no connection to Spotify
+    #[cfg(feature = "streaming")]
+    offline_player: Arc<Mutex<Option<offline_player::OfflinePlayer>>>,
+    volume_fade: Arc<Mutex<Option<fade::VolumeFade>>>,
+    /// a lock held while checking a volume fade and writing its volume, so that a fade's volume
+    /// is never written after the fade is interrupted
+    volume_fade_write: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl Deref for Client {
@@ -60,6 +87,56 @@ fn market_query() -> Query<'static> {
     Query::from([("market", "from_token")])
 }
 
+/// Get lyrics of a track from a local `.lrc` file in the `lyrics_folder`.
+///
+/// The file is looked up by the track's ID (`<track_id>.lrc`), then by
+/// the track's name and artists (`<artists> - <track>.lrc`).
+fn local_lyrics(track: &Track) -> Result<Option<Lyrics>> {
+    let Some(folder) = &config::get_config().app_config.lyrics_folder else {
+        return Ok(None);
+    };
+
+    let mut file_names = vec![
+        format!("{}.lrc", track.id.id()),
+        crate::utils::lrc_file_name(&track.name, &track.artists_info()),
+    ];
+    if track.artists.len() > 1 {
+        file_names.push(crate::utils::lrc_file_name(
+            &track.name,
+            &track.artists[0].name,
+        ));
+    }
+
+    for file_name in file_names {
+        let path = folder.join(file_name);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                tracing::info!("Loaded lyrics from {}", path.display());
+                return Ok(Some(Lyrics::from_lyric_finder(
+                    lyric_finder::lrc::parse(&content),
+                    LyricsProvider::Local,
+                )));
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err).with_context(|| format!("read lyrics file {}", path.display()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Construct a query to look up a track's lyrics from external lyrics providers
+fn lyrics_query(track: &Track) -> lyric_finder::TrackQuery {
+    lyric_finder::TrackQuery {
+        track: track.name.clone(),
+        artists: track.artists_info(),
+        album: track.album.as_ref().map(|a| a.name.clone()),
+        duration: Some(track.duration),
+    }
+}
+
 impl Client {
     /// Construct a new client
     pub fn new(auth_config: AuthConfig) -> Self {
@@ -70,6 +147,12 @@ impl Client {
 
             #[cfg(feature = "streaming")]
             stream_conn: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "streaming")]
+            mixer: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "streaming")]
+            offline_player: Arc::new(Mutex::new(None)),
+            volume_fade: Arc::new(Mutex::new(None)),
+            volume_fade_write: Arc::new(tokio::sync::Mutex::new(())),
         }
     }
 
@@ -131,6 +214,32 @@ impl Client {
         let creds = auth::get_creds(&self.auth_config, reauth, true).context("get credentials")?;
         *self.session.lock().await = Some(session.clone());
 
+        if let Err(err) = self.connect_session(state, &session, creds).await {
+            // invalidate the session, so that a new session is created upon the next request
+            session.shutdown();
+            return Err(err);
+        }
+
+        #[cfg(feature = "streaming")]
+        self.stop_offline_player();
+
+        if let Some(state) = state {
+            // reset the application's caches
+            state.data.write().caches = MemoryCaches::new();
+            self.initialize_playback(state);
+        }
+
+        Ok(())
+    }
+
+    /// Connect a new session to Spotify and refresh the client's token
+    #[cfg_attr(not(feature = "streaming"), allow(unused_variables))]
+    async fn connect_session(
+        &self,
+        state: Option<&SharedState>,
+        session: &librespot_core::Session,
+        creds: librespot_core::authentication::Credentials,
+    ) -> Result<()> {
         #[allow(unused_mut)]
         let mut connected = false;
 
@@ -154,15 +263,7 @@ impl Client {
 
         tracing::info!("Used a new session for Spotify client.");
 
-        self.refresh_token().await.context("refresh auth token")?;
-
-        if let Some(state) = state {
-            // reset the application's caches
-            state.data.write().caches = MemoryCaches::new();
-            self.initialize_playback(state);
-        }
-
-        Ok(())
+        self.refresh_token().await.context("refresh auth token")
     }
 
     /// Check if the current session is valid and if invalid, create a new session
@@ -184,8 +285,10 @@ impl Client {
         session: librespot_core::Session,
         creds: librespot_core::authentication::Credentials,
     ) -> Result<()> {
-        let new_conn =
+        offline::prune_audio_cache(&state);
+        let (new_conn, mixer) =
             crate::streaming::new_connection(self.clone(), state, session, creds).await?;
+        *self.mixer.lock() = Some(mixer);
         let mut stream_conn = self.stream_conn.lock();
         // shutdown old streaming connection and replace it with a new connection
         if let Some(conn) = stream_conn.as_ref() {
@@ -203,12 +306,22 @@ impl Client {
         request: PlayerRequest,
         mut playback: Option<PlaybackMetadata>,
     ) -> Result<Option<PlaybackMetadata>> {
+        // a new player request interrupts the volume fade in progress
+        self.interrupt_volume_fade().await?;
+
         // handle requests that don't require an active playback
         match request {
             PlayerRequest::TransferPlayback(device_id, force_play) => {
                 // `TransferPlayback` needs to be handled separately from other player requests
                 // because `TransferPlayback` doesn't require an active playback
-                self.transfer_playback(&device_id, Some(force_play)).await?;
+                let transfer = self.transfer_playback(&device_id, Some(force_play));
+                match playback {
+                    // the playback is faded out on the previous device
+                    Some(ref p) if p.is_playing => {
+                        self.with_volume_fade(p, true, false, transfer).await?;
+                    }
+                    _ => transfer.await?,
+                }
                 tracing::info!("Transferred playback to device with id={}", device_id);
                 return Ok(None);
             }
@@ -232,27 +345,47 @@ impl Client {
         let mut playback = playback.context("no playback found")?;
         let device_id = playback.device_id.as_deref();
 
+        let is_playing = playback.is_playing;
+
         match request {
-            PlayerRequest::NextTrack => self.next_track(device_id).await?,
-            PlayerRequest::PreviousTrack => self.previous_track(device_id).await?,
+            PlayerRequest::NextTrack => {
+                self.with_volume_fade(
+                    &playback,
+                    is_playing,
+                    is_playing,
+                    self.next_track(device_id),
+                )
+                .await?;
+            }
+            PlayerRequest::PreviousTrack => {
+                let previous_track = self.previous_track(device_id);
+                self.with_volume_fade(&playback, is_playing, is_playing, previous_track)
+                    .await?;
+            }
             PlayerRequest::Resume => {
                 if !playback.is_playing {
-                    self.resume_playback(device_id, None).await?;
+                    let resume = self.resume_playback(device_id, None);
+                    self.with_volume_fade(&playback, false, true, resume)
+                        .await?;
                     playback.is_playing = true;
                 }
             }
 
             PlayerRequest::Pause => {
                 if playback.is_playing {
-                    self.pause_playback(device_id).await?;
+                    let pause = self.pause_playback(device_id);
+                    self.with_volume_fade(&playback, true, false, pause).await?;
                     playback.is_playing = false;
                 }
             }
             PlayerRequest::ResumePause => {
                 if playback.is_playing {
-                    self.pause_playback(device_id).await?;
+                    let pause = self.pause_playback(device_id);
+                    self.with_volume_fade(&playback, true, false, pause).await?;
                 } else {
-                    self.resume_playback(device_id, None).await?;
+                    let resume = self.resume_playback(device_id, None);
+                    self.with_volume_fade(&playback, false, true, resume)
+                        .await?;
                 }
                 playback.is_playing = !playback.is_playing;
             }
@@ -328,10 +461,32 @@ impl Client {
                     .category_playlists
                     .insert(category.id, playlists);
             }
-            ClientRequest::GetLyrics { track_id } => {
-                let uri = track_id.uri();
+            ClientRequest::GetLyrics { track } => {
+                let uri = track.id.uri();
                 if !state.data.read().caches.lyrics.contains_key(&uri) {
-                    let lyrics = self.lyrics(track_id).await?;
+                    let mut lyrics = self.lyrics(&track).await?;
+                    let configs = config::get_config();
+                    if let Some(cmd) = configs.app_config.lyrics_translation_command.clone() {
+                        if let Some(l) = lyrics.as_mut() {
+                            // the translation command is blocking, so run it in a separate thread
+                            let input = l.translation_input();
+                            let translate =
+                                tokio::task::spawn_blocking(move || cmd.execute_with_stdin(input));
+                            // fall back to the untranslated lyrics if the command fails or hangs
+                            match tokio::time::timeout(LYRICS_TRANSLATION_TIMEOUT, translate).await
+                            {
+                                Ok(Ok(Ok(output))) => l.set_translations(&output),
+                                Ok(Ok(Err(err))) => {
+                                    tracing::warn!("Failed to translate lyrics: {err:#}");
+                                }
+                                Ok(Err(err)) => return Err(err.into()),
+                                Err(_) => tracing::warn!(
+                                    "Lyrics translation command timed out after {}s",
+                                    LYRICS_TRANSLATION_TIMEOUT.as_secs()
+                                ),
+                            }
+                        }
+                    }
                     state
                         .data
                         .write()
@@ -344,6 +499,14 @@ impl Client {
             ClientRequest::RestartIntegratedClient => {
                 self.new_session(Some(state), false).await?;
             }
+            #[cfg(feature = "streaming")]
+            ClientRequest::PinContext(id) => {
+                self.pin_context(state, id).await?;
+            }
+            #[cfg(feature = "streaming")]
+            ClientRequest::UnpinContext(uri) => {
+                offline::unpin_context(state, &uri)?;
+            }
             ClientRequest::GetCurrentUser => {
                 let user = self.current_user().await?;
                 state.data.write().user_data.user = Some(user);
@@ -354,6 +517,22 @@ impl Client {
                 state.player.write().buffered_playback = playback;
                 self.update_playback(state);
             }
+            ClientRequest::ConnectDevice(name) => {
+                let device = self.find_device(&name).await?;
+                let (playback, is_playing) = {
+                    let player = state.player.read();
+                    let is_playing = player.playback.as_ref().is_some_and(|p| p.is_playing);
+                    (player.buffered_playback.clone(), is_playing)
+                };
+                let playback = self
+                    .handle_player_request(
+                        PlayerRequest::TransferPlayback(device.id, is_playing),
+                        playback,
+                    )
+                    .await?;
+                state.player.write().buffered_playback = playback;
+                self.update_playback(state);
+            }
             ClientRequest::GetCurrentPlayback => {
                 self.retrieve_current_playback(state, true).await?;
             }
@@ -523,8 +702,14 @@ impl Client {
             ClientRequest::AddPlayableToQueue(playable_id) => {
                 self.add_item_to_queue(playable_id, None).await?;
             }
-            ClientRequest::AddPlayableToPlaylist(playlist_id, playable_id) => {
-                self.add_item_to_playlist(state, playlist_id, playable_id)
+            ClientRequest::AddPlayablesToQueue(playable_ids) => {
+                // the items are added one after another to keep their order in the queue
+                for playable_id in playable_ids {
+                    self.add_item_to_queue(playable_id, None).await?;
+                }
+            }
+            ClientRequest::AddPlayablesToPlaylist(playlist_id, playable_ids) => {
+                self.add_items_to_playlist(state, playlist_id, &playable_ids)
                     .await?;
             }
             ClientRequest::AddAlbumToQueue(album_id) => {
@@ -537,10 +722,16 @@ impl Client {
                     }
                 }
             }
-            ClientRequest::DeleteTrackFromPlaylist(playlist_id, track_id) => {
-                self.delete_track_from_playlist(state, playlist_id, track_id)
+            ClientRequest::DeleteTracksFromPlaylist(playlist_id, track_ids) => {
+                self.delete_tracks_from_playlist(state, playlist_id, &track_ids)
                     .await?;
             }
+            ClientRequest::AddTracksToLiked(tracks) => {
+                self.add_tracks_to_liked(state, tracks).await?;
+            }
+            ClientRequest::DeleteTracksFromLiked(track_ids) => {
+                self.delete_tracks_from_liked(state, &track_ids).await?;
+            }
             ClientRequest::AddToLibrary(item) => {
                 self.add_to_library(state, item).await?;
             }
@@ -568,6 +759,25 @@ impl Client {
                 )
                 .await?;
             }
+            ClientRequest::MovePlaylistItemRanges {
+                playlist_id,
+                ranges,
+                up,
+            } => {
+                // the ranges are moved one after another, as each move depends on the previous ones
+                for (range_start, range_length) in ranges {
+                    let insert_index = if up { range_start - 1 } else { range_start + 1 };
+                    self.reorder_playlist_items(
+                        state,
+                        playlist_id.as_ref(),
+                        insert_index,
+                        range_start,
+                        Some(range_length),
+                        None,
+                    )
+                    .await?;
+                }
+            }
             ClientRequest::CreatePlaylist {
                 playlist_name,
                 public,
@@ -602,8 +812,89 @@ impl Client {
         Ok(())
     }
 
-    /// Get lyrics of a given track, return None if no lyrics is available
-    pub async fn lyrics(&self, track_id: TrackId<'static>) -> Result<Option<Lyrics>> {
+    /// Get lyrics of a given track, return None if no lyrics is available.
+    ///
+    /// The lyrics providers specified in the `lyrics_providers` config option
+    /// are queried in order until one of them returns the track's lyrics.
+    ///
+    /// Results of non-local providers are persisted in the lyrics file cache,
+    /// which is consulted before querying the first non-local provider.
+    pub async fn lyrics(&self, track: &Track) -> Result<Option<Lyrics>> {
+        let cache_folder = &config::get_config().cache_folder;
+        let track_id = track.id.id();
+        let query = lyrics_query(track);
+
+        let mut last_err = None;
+        // whether the lyrics file cache has been consulted
+        let mut file_cache_checked = false;
+        // whether the file cache has a "no lyrics found" result for non-local providers
+        let mut file_cache_miss = false;
+
+        for provider in &config::get_config().app_config.lyrics_providers {
+            let result = if *provider == LyricsProvider::Local {
+                local_lyrics(track)
+            } else {
+                if !file_cache_checked {
+                    file_cache_checked = true;
+                    if let Some(entry) = load_lyrics_from_file_cache(cache_folder, track_id) {
+                        tracing::info!("Loaded lyrics of track {} from file cache", track.id);
+                        match entry.lyrics {
+                            Some(lyrics) => return Ok(Some(lyrics)),
+                            None => file_cache_miss = true,
+                        }
+                    }
+                }
+                if file_cache_miss {
+                    continue;
+                }
+
+                match provider {
+                    LyricsProvider::Spotify => self.spotify_lyrics(track.id.as_ref()).await,
+                    _ => self.external_lyrics(*provider, &query).await,
+                }
+            };
+            match result {
+                Ok(Some(lyrics)) => {
+                    if lyrics.provider != LyricsProvider::Local {
+                        // failing to cache the lyrics doesn't prevent showing them
+                        if let Err(err) = store_lyrics_into_file_cache(
+                            cache_folder,
+                            track_id,
+                            Some(lyrics.clone()),
+                        ) {
+                            tracing::warn!("Failed to store lyrics into the cache folder: {err:#}");
+                        }
+                    }
+                    return Ok(Some(lyrics));
+                }
+                Ok(None) => {
+                    tracing::info!("No lyrics found for track {} using {provider:?}", track.id);
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to get lyrics for track {} using {provider:?}: {err:#}",
+                        track.id
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        // only report an error if no provider could be queried successfully, so that
+        // the request can be retried later instead of caching a "not found" result
+        if let Some(err) = last_err {
+            return Err(err);
+        }
+        if file_cache_checked && !file_cache_miss {
+            if let Err(err) = store_lyrics_into_file_cache(cache_folder, track_id, None) {
+                tracing::warn!("Failed to store lyrics into the cache folder: {err:#}");
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get lyrics of a given track from Spotify's lyrics service
+    async fn spotify_lyrics(&self, track_id: TrackId<'_>) -> Result<Option<Lyrics>> {
         let session = self.session().await;
         let id = librespot_core::spotify_id::SpotifyId::from_uri(&track_id.uri())?;
         match librespot_metadata::Lyrics::get(&session, &id).await {
@@ -618,6 +909,29 @@ impl Client {
         }
     }
 
+    /// Get lyrics of a track from an external lyrics provider
+    async fn external_lyrics(
+        &self,
+        provider: LyricsProvider,
+        query: &lyric_finder::TrackQuery,
+    ) -> Result<Option<Lyrics>> {
+        let finder: Box<dyn lyric_finder::LyricsProvider> = match provider {
+            LyricsProvider::Lrclib => Box::new(lyric_finder::LrclibClient::from_http_client(
+                &self.http,
+                &config::get_config().app_config.lrclib_base_url,
+            )),
+            LyricsProvider::Genius => Box::new(lyric_finder::Client::from_http_client(&self.http)),
+            LyricsProvider::Local | LyricsProvider::Spotify => {
+                anyhow::bail!("{provider:?} is not an external provider")
+            }
+        };
+
+        Ok(finder
+            .find_lyrics(query)
+            .await?
+            .map(|lyrics| Lyrics::from_lyric_finder(lyrics, provider)))
+    }
+
     /// Get user available devices
     // This is a custom API to replace `rspotify::device` API to support Spotify Connect feature
     pub async fn available_devices(&self) -> Result<Vec<rspotify::model::Device>> {
@@ -631,6 +945,16 @@ impl Client {
             .devices)
     }
 
+    /// Find an available device by its name (case-insensitive)
+    pub async fn find_device(&self, name: &str) -> Result<Device> {
+        self.available_devices()
+            .await?
+            .into_iter()
+            .filter_map(Device::try_from_device)
+            .find(|d| d.name.eq_ignore_ascii_case(name))
+            .with_context(|| format!("no device with name={name} found"))
+    }
+
     pub fn update_playback(&self, state: &SharedState) {
         // After handling a request changing the player's playback,
         // update the playback state by making multiple get-playback requests.
@@ -1059,44 +1383,54 @@ impl Client {
             .await?)
     }
 
-    /// Add a playable item to a playlist
-    pub async fn add_item_to_playlist(
+    /// Add playable items to a playlist
+    pub async fn add_items_to_playlist(
         &self,
         state: &SharedState,
         playlist_id: PlaylistId<'_>,
-        playable_id: PlayableId<'_>,
+        playable_ids: &[PlayableId<'_>],
     ) -> Result<()> {
-        // remove all the occurrences of the track to ensure no duplication in the playlist
-        self.playlist_remove_all_occurrences_of_items(
-            playlist_id.as_ref(),
-            [playable_id.as_ref()],
-            None,
-        )
-        .await?;
+        // remove all the occurrences of the items to ensure no duplication in the playlist
+        for ids in playable_ids.chunks(PLAYLIST_ITEMS_LIMIT) {
+            self.playlist_remove_all_occurrences_of_items(
+                playlist_id.as_ref(),
+                ids.iter().map(PlayableId::as_ref),
+                None,
+            )
+            .await?;
+        }
 
-        self.playlist_add_items(playlist_id.as_ref(), [playable_id.as_ref()], None)
+        for ids in playable_ids.chunks(PLAYLIST_ITEMS_LIMIT) {
+            self.playlist_add_items(
+                playlist_id.as_ref(),
+                ids.iter().map(PlayableId::as_ref),
+                None,
+            )
             .await?;
+        }
 
-        // After adding a new track to a playlist, remove the cache of that playlist to force refetching new data
+        // After adding new tracks to a playlist, remove the cache of that playlist to force refetching new data
         state.data.write().caches.context.remove(&playlist_id.uri());
 
         Ok(())
     }
 
-    /// Remove a track from a playlist
-    pub async fn delete_track_from_playlist(
+    /// Remove tracks from a playlist
+    pub async fn delete_tracks_from_playlist(
         &self,
         state: &SharedState,
         playlist_id: PlaylistId<'_>,
-        track_id: TrackId<'_>,
+        track_ids: &[TrackId<'_>],
     ) -> Result<()> {
-        // remove all the occurrences of the track to ensure no duplication in the playlist
-        self.playlist_remove_all_occurrences_of_items(
-            playlist_id.as_ref(),
-            [PlayableId::Track(track_id.as_ref())],
-            None,
-        )
-        .await?;
+        // remove all the occurrences of the tracks to ensure no duplication in the playlist
+        for ids in track_ids.chunks(PLAYLIST_ITEMS_LIMIT) {
+            self.playlist_remove_all_occurrences_of_items(
+                playlist_id.as_ref(),
+                ids.iter().map(|id| PlayableId::Track(id.as_ref())),
+                None,
+            )
+            .await?;
+        }
 
         // After making a delete request, update the playlist in-memory data stored inside the app caches.
         if let Some(Context::Playlist { tracks, .. }) = state
@@ -1106,12 +1440,57 @@ impl Client {
             .context
             .get_mut(&playlist_id.uri())
         {
-            tracks.retain(|t| t.id != track_id);
+            tracks.retain(|t| !track_ids.contains(&t.id));
         }
 
         Ok(())
     }
 
+    /// Add tracks to the user's liked tracks, skipping the already liked ones
+    async fn add_tracks_to_liked(&self, state: &SharedState, tracks: Vec<Track>) -> Result<()> {
+        for tracks in tracks.chunks(SAVED_TRACKS_LIMIT) {
+            let contains = self
+                .current_user_saved_tracks_contains(tracks.iter().map(|t| t.id.as_ref()))
+                .await?;
+            let tracks = tracks
+                .iter()
+                .zip(contains)
+                .filter_map(|(t, contains)| (!contains).then_some(t))
+                .collect::<Vec<_>>();
+            if tracks.is_empty() {
+                continue;
+            }
+
+            self.current_user_saved_tracks_add(tracks.iter().map(|t| t.id.as_ref()))
+                .await?;
+            // update the in-memory `user_data`
+            let mut data = state.data.write();
+            for track in tracks {
+                data.user_data
+                    .saved_tracks
+                    .insert(track.id.uri(), track.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove tracks from the user's liked tracks
+    async fn delete_tracks_from_liked(
+        &self,
+        state: &SharedState,
+        track_ids: &[TrackId<'_>],
+    ) -> Result<()> {
+        for ids in track_ids.chunks(SAVED_TRACKS_LIMIT) {
+            self.current_user_saved_tracks_delete(ids.iter().map(TrackId::as_ref))
+                .await?;
+            let mut data = state.data.write();
+            for id in ids {
+                data.user_data.saved_tracks.remove(&id.uri());
+            }
+        }
+        Ok(())
+    }
+
     /// Reorder items in a playlist
     async fn reorder_playlist_items(
         &self,
@@ -1122,8 +1501,9 @@ impl Client {
         range_length: Option<usize>,
         snapshot_id: Option<&str>,
     ) -> Result<()> {
+        // `insert_index` is the range's first item's index after the reorder
         let insert_before = if insert_index > range_start {
-            insert_index + 1
+            insert_index + range_length.unwrap_or(1)
         } else {
             insert_index
         };
@@ -1145,8 +1525,13 @@ impl Client {
             .context
             .get_mut(&playlist_id.uri())
         {
-            let track = tracks.remove(range_start);
-            tracks.insert(insert_index, track);
+            let range_length = range_length.unwrap_or(1);
+            if range_start.max(insert_index) + range_length <= tracks.len() {
+                let range = tracks
+                    .drain(range_start..range_start + range_length)
+                    .collect::<Vec<_>>();
+                tracks.splice(insert_index..insert_index, range);
+            }
         }
 
         Ok(())
@@ -1601,6 +1986,7 @@ impl Client {
 
             new_playback
         };
+        state.publish_player_state_changes(false);
 
         if !new_playback {
             return Ok(());
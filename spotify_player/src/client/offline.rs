@@ -0,0 +1,410 @@
+//! Pinning playlists and albums for offline listening.
+//!
+//! The audio files of a pinned context's tracks are prefetched into the integrated
+//! player's audio cache and are excluded from the audio cache's eviction.
+//! Without a connection to Spotify, pinned tracks are played from the audio cache
+//! by an [`OfflinePlayer`](super::offline_player::OfflinePlayer).
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context as _, Result};
+use librespot_audio::AudioFile;
+use librespot_core::{spotify_id::SpotifyId, FileId, Session};
+use librespot_metadata::{
+    audio::{AudioFileFormat, AudioFiles},
+    Metadata,
+};
+use rspotify::prelude::Id;
+
+use crate::config;
+use crate::state::{
+    audio_cache_file_path, store_data_into_file_cache, Context, ContextId, FileCacheKey,
+    PinnedContext, PinnedTrack, Playback, SharedState, Track, TTL_CACHE_DURATION,
+};
+
+use super::{
+    offline_player::{OfflinePlayer, OfflinePlayerRequest},
+    Client, ClientRequest, PlayerRequest,
+};
+
+impl Client {
+    /// Pin a playlist or an album for offline listening by prefetching
+    /// its tracks' audio files into the audio cache
+    pub async fn pin_context(&self, state: &SharedState, id: ContextId) -> Result<()> {
+        let configs = config::get_config();
+        if !configs.app_config.device.audio_cache {
+            anyhow::bail!("pinning requires the device's `audio_cache` to be enabled");
+        }
+
+        let uri = id.uri();
+        let (name, tracks) = match id {
+            ContextId::Playlist(id) => match self.playlist_context(id).await? {
+                Context::Playlist { playlist, tracks } => (playlist.name, tracks),
+                _ => unreachable!("playlist context"),
+            },
+            ContextId::Album(id) => match self.album_context(id).await? {
+                Context::Album { album, tracks } => (album.name, tracks),
+                _ => unreachable!("album context"),
+            },
+            _ => anyhow::bail!("only playlists and albums can be pinned"),
+        };
+
+        // the positions of tracks in the context, which keep the pinned tracks in the context's order
+        let positions = tracks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.id.uri(), i))
+            .collect::<HashMap<_, _>>();
+        let position = |t: &PinnedTrack| positions.get(&t.track.id.uri()).copied();
+
+        // when re-pinning a context, the tracks whose audio files are still in
+        // the audio cache are kept, and only the other tracks are prefetched
+        let audio_folder = configs.cache_folder.join("audio");
+        let mut pinned_tracks = state
+            .data
+            .read()
+            .user_data
+            .pinned_contexts
+            .get(&uri)
+            .map(|c| c.tracks.clone())
+            .unwrap_or_default();
+        pinned_tracks.retain(|t| {
+            position(t).is_some() && audio_cache_file_path(&audio_folder, &t.file_id).exists()
+        });
+        pinned_tracks.sort_by_key(position);
+        let tracks = tracks
+            .into_iter()
+            .filter(|t| !pinned_tracks.iter().any(|p| p.track.id == t.id))
+            .collect::<Vec<_>>();
+
+        tracing::info!(
+            "Pinning {uri} ({} tracks, {} to prefetch) for offline listening",
+            positions.len(),
+            tracks.len()
+        );
+        state.data.write().user_data.pinned_contexts.insert(
+            uri.clone(),
+            PinnedContext {
+                name,
+                total: positions.len(),
+                tracks: pinned_tracks,
+                failed: 0,
+            },
+        );
+
+        let session = self.session().await;
+        let bitrate = configs.app_config.device.bitrate;
+        for track in tracks {
+            let result = prefetch_track(&session, &track, bitrate).await;
+
+            let mut data = state.data.write();
+            let Some(pinned) = data.user_data.pinned_contexts.get_mut(&uri) else {
+                tracing::info!("{uri} has been unpinned, stop prefetching its tracks");
+                return Ok(());
+            };
+            match result {
+                Ok(track) => {
+                    let i = pinned
+                        .tracks
+                        .partition_point(|t| position(t) < position(&track));
+                    pinned.tracks.insert(i, track);
+                }
+                Err(err) => {
+                    tracing::warn!("Failed to prefetch track {}: {err:#}", track.id.uri());
+                    pinned.failed += 1;
+                }
+            }
+            store_data_into_file_cache(
+                FileCacheKey::PinnedContexts,
+                &configs.cache_folder,
+                &data.user_data.pinned_contexts,
+            )
+            .context("store pinned contexts into the cache folder")?;
+        }
+
+        tracing::info!("Finished prefetching tracks of {uri}");
+        prune_audio_cache(state);
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Handle a request without a connection to Spotify, returning whether the request is handled.
+    ///
+    /// Pinned contexts can be browsed and their tracks are played from the audio cache.
+    pub fn handle_offline_request(
+        &self,
+        state: &SharedState,
+        request: &ClientRequest,
+    ) -> Result<bool> {
+        let request = match request {
+            ClientRequest::GetContext(id) => {
+                let uri = id.uri();
+                let data = state.data.read();
+                let Some(pinned) = data.user_data.pinned_contexts.get(&uri) else {
+                    return Ok(false);
+                };
+                let context = Context::Tracks {
+                    tracks: pinned.tracks.iter().map(|t| t.track.clone()).collect(),
+                    desc: format!("{} (offline)", pinned.name),
+                };
+                drop(data);
+                state
+                    .data
+                    .write()
+                    .caches
+                    .context
+                    .insert(uri, context, *TTL_CACHE_DURATION);
+                return Ok(true);
+            }
+            ClientRequest::Player(PlayerRequest::StartPlayback(playback, _)) => {
+                let Some((tracks, start)) = offline_queue(state, playback) else {
+                    return Ok(false);
+                };
+                OfflinePlayerRequest::Play(tracks, start)
+            }
+            ClientRequest::Player(PlayerRequest::ResumePause) => OfflinePlayerRequest::ResumePause,
+            ClientRequest::Player(PlayerRequest::Resume) => OfflinePlayerRequest::Resume,
+            ClientRequest::Player(PlayerRequest::Pause) => OfflinePlayerRequest::Pause,
+            ClientRequest::Player(PlayerRequest::NextTrack) => OfflinePlayerRequest::NextTrack,
+            ClientRequest::Player(PlayerRequest::PreviousTrack) => {
+                OfflinePlayerRequest::PreviousTrack
+            }
+            ClientRequest::Player(PlayerRequest::Volume(volume)) => {
+                OfflinePlayerRequest::Volume(*volume)
+            }
+            _ => return Ok(false),
+        };
+
+        let mut player = self.offline_player.lock();
+        if player.is_none() {
+            // the offline player is only started to play pinned tracks
+            if !matches!(request, OfflinePlayerRequest::Play(..)) {
+                return Ok(false);
+            }
+            tracing::info!("Starting an offline player to play pinned tracks from the audio cache");
+            *player = Some(OfflinePlayer::new(state)?);
+        }
+        player.as_ref().expect("offline player").send(request)?;
+        Ok(true)
+    }
+
+    /// Stop the offline player (if any), e.g. after reconnecting to Spotify
+    pub fn stop_offline_player(&self) {
+        if self.offline_player.lock().take().is_some() {
+            tracing::info!("Stopped the offline player");
+        }
+    }
+}
+
+/// Get the pinned tracks to play offline for a playback and the index of the first track
+fn offline_queue(state: &SharedState, playback: &Playback) -> Option<(Vec<PinnedTrack>, usize)> {
+    let data = state.data.read();
+    let (tracks, offset) = match playback {
+        Playback::Context(id, offset) => (
+            data.user_data
+                .pinned_contexts
+                .get(&id.uri())?
+                .tracks
+                .clone(),
+            offset,
+        ),
+        Playback::URIs(ids, offset) => {
+            // the tracks are played offline only if all of them are pinned
+            let tracks = ids
+                .iter()
+                .map(|id| {
+                    let uri = id.uri();
+                    data.user_data
+                        .pinned_contexts
+                        .values()
+                        .flat_map(|c| c.tracks.iter())
+                        .find(|t| t.track.id.uri() == uri)
+                        .cloned()
+                })
+                .collect::<Option<Vec<_>>>()?;
+            (tracks, offset)
+        }
+    };
+    if tracks.is_empty() {
+        return None;
+    }
+
+    let start = match offset {
+        Some(rspotify::model::Offset::Uri(uri)) => {
+            tracks.iter().position(|t| t.track.id.uri() == *uri)?
+        }
+        // a position offset is the index of the first track to play
+        Some(rspotify::model::Offset::Position(position)) => {
+            usize::try_from(position.num_milliseconds()).ok()?
+        }
+        None => 0,
+    };
+    Some((tracks, start))
+}
+
+/// Unpin a playlist or an album, its audio files become subject to the audio cache's eviction
+pub fn unpin_context(state: &SharedState, uri: &str) -> Result<()> {
+    let mut data = state.data.write();
+    data.user_data.pinned_contexts.remove(uri);
+    store_data_into_file_cache(
+        FileCacheKey::PinnedContexts,
+        &config::get_config().cache_folder,
+        &data.user_data.pinned_contexts,
+    )
+    .context("store pinned contexts into the cache folder")?;
+    Ok(())
+}
+
+/// Prefetch a track's audio file into the audio cache, together with the key to decrypt the file
+async fn prefetch_track(session: &Session, track: &Track, bitrate: u16) -> Result<PinnedTrack> {
+    let spotify_id = SpotifyId::from_uri(&track.id.uri())?;
+    let metadata = librespot_metadata::Track::get(session, &spotify_id).await?;
+
+    // the same audio file as the one played by the integrated player
+    // is selected based on the device's bitrate
+    let formats = match bitrate {
+        96 => [
+            AudioFileFormat::OGG_VORBIS_96,
+            AudioFileFormat::MP3_96,
+            AudioFileFormat::OGG_VORBIS_160,
+            AudioFileFormat::MP3_160,
+            AudioFileFormat::MP3_256,
+            AudioFileFormat::OGG_VORBIS_320,
+            AudioFileFormat::MP3_320,
+        ],
+        160 => [
+            AudioFileFormat::OGG_VORBIS_160,
+            AudioFileFormat::MP3_160,
+            AudioFileFormat::OGG_VORBIS_96,
+            AudioFileFormat::MP3_96,
+            AudioFileFormat::MP3_256,
+            AudioFileFormat::OGG_VORBIS_320,
+            AudioFileFormat::MP3_320,
+        ],
+        _ => [
+            AudioFileFormat::OGG_VORBIS_320,
+            AudioFileFormat::MP3_320,
+            AudioFileFormat::MP3_256,
+            AudioFileFormat::OGG_VORBIS_160,
+            AudioFileFormat::MP3_160,
+            AudioFileFormat::OGG_VORBIS_96,
+            AudioFileFormat::MP3_96,
+        ],
+    };
+    let (format, file_id): (AudioFileFormat, FileId) = formats
+        .iter()
+        .find_map(|format| metadata.files.get(format).map(|id| (*format, *id)))
+        .context("track is not available in any supported format")?;
+
+    let mut file = AudioFile::open(session, file_id, 0).await?;
+    if !file.is_cached() {
+        // Reading the whole file downloads it, and the file is stored into
+        // the audio cache once completely downloaded
+        file.get_stream_loader_controller()?.set_stream_mode();
+        tokio::task::spawn_blocking(move || std::io::copy(&mut file, &mut std::io::sink()))
+            .await??;
+    }
+
+    // the key is stored to play the track without a connection to Spotify
+    let key = session
+        .audio_key()
+        .request(spotify_id, file_id)
+        .await
+        .context("request the audio file's key")?;
+
+    Ok(PinnedTrack {
+        track: track.clone(),
+        file_id: file_id.to_base16()?,
+        key: key.0.iter().fold(String::new(), |mut s, b| {
+            let _ = write!(s, "{b:02x}");
+            s
+        }),
+        is_ogg_vorbis: AudioFiles::is_ogg_vorbis(format),
+    })
+}
+
+/// Remove the least recently used audio files from the audio cache until
+/// its size is under the configured limit. Audio files of pinned contexts are never removed.
+///
+/// The audio cache is pruned in a blocking task, and pruning is skipped
+/// if the previous pruning is still in progress.
+pub fn prune_audio_cache(state: &SharedState) {
+    static IS_PRUNING: AtomicBool = AtomicBool::new(false);
+
+    let configs = config::get_config();
+    let Some(limit_mb) = configs.app_config.device.audio_cache_size_limit_mb else {
+        return;
+    };
+    let audio_folder = configs.cache_folder.join("audio");
+    let pinned = state
+        .data
+        .read()
+        .user_data
+        .pinned_contexts
+        .values()
+        .flat_map(|c| c.tracks.iter())
+        .map(|t| audio_cache_file_path(&audio_folder, &t.file_id))
+        .collect::<HashSet<_>>();
+
+    if IS_PRUNING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    tokio::task::spawn_blocking(move || {
+        match prune_audio_folder(&audio_folder, limit_mb * 1024 * 1024, &pinned) {
+            Ok(0) => {}
+            Ok(removed) => tracing::info!("Removed {removed} audio files from the audio cache"),
+            Err(err) => tracing::warn!("Failed to prune the audio cache: {err:#}"),
+        }
+        IS_PRUNING.store(false, Ordering::Release);
+    });
+}
+
+fn prune_audio_folder(folder: &Path, limit: u64, pinned: &HashSet<PathBuf>) -> Result<usize> {
+    fn collect_files(
+        folder: &Path,
+        files: &mut Vec<(PathBuf, std::time::SystemTime, u64)>,
+    ) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(folder)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                collect_files(&entry.path(), files)?;
+            } else {
+                // the same access time as `librespot`'s audio cache size limiter
+                let accessed = metadata
+                    .accessed()
+                    .or_else(|_| metadata.modified())
+                    .unwrap_or_else(|_| std::time::SystemTime::now());
+                files.push((entry.path(), accessed, metadata.len()));
+            }
+        }
+        Ok(())
+    }
+
+    if !folder.exists() {
+        return Ok(0);
+    }
+    let mut files = vec![];
+    collect_files(folder, &mut files)?;
+
+    let mut size = files.iter().map(|(_, _, size)| size).sum::<u64>();
+    files.sort_by_key(|(_, accessed, _)| *accessed);
+
+    let mut removed = 0;
+    for (path, _, file_size) in files {
+        if size <= limit {
+            break;
+        }
+        if pinned.contains(&path) {
+            continue;
+        }
+        std::fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+        size -= file_size;
+        removed += 1;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,322 @@
+//! Playing pinned tracks from the audio cache without a connection to Spotify.
+
+use std::io::Read;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+
+use anyhow::{Context as _, Result};
+use librespot_audio::AudioDecrypt;
+use librespot_core::audio_key::AudioKey;
+use librespot_metadata::audio::AudioFileFormat;
+use librespot_playback::{
+    audio_backend::Sink,
+    convert::Converter,
+    decoder::{AudioDecoder, AudioPacket, SymphoniaDecoder},
+    NUM_CHANNELS, SAMPLE_RATE,
+};
+
+use crate::config;
+use crate::state::{PinnedTrack, SharedState, State};
+
+/// the end of the Spotify-specific header of an Ogg Vorbis audio file,
+/// which is skipped when decoding the file
+const SPOTIFY_OGG_HEADER_END: usize = 0xa7;
+
+#[derive(Debug)]
+pub enum OfflinePlayerRequest {
+    /// play a list of tracks starting from the track at an index
+    Play(Vec<PinnedTrack>, usize),
+    ResumePause,
+    Resume,
+    Pause,
+    NextTrack,
+    PreviousTrack,
+    Volume(u8),
+}
+
+/// A player playing pinned tracks from the audio cache in a dedicated thread.
+///
+/// The player's thread stops when the player is dropped.
+pub struct OfflinePlayer {
+    sender: mpsc::Sender<OfflinePlayerRequest>,
+    state: SharedState,
+}
+
+impl OfflinePlayer {
+    pub fn new(state: &SharedState) -> Result<Self> {
+        let new_sink = crate::streaming::sink_builder(state)?;
+        let (sender, receiver) = mpsc::channel();
+        let thread_state = state.clone();
+        std::thread::Builder::new()
+            .name("offline-player".to_string())
+            .spawn(move || run(new_sink(), &receiver, &thread_state))
+            .context("spawn the offline player's thread")?;
+        state
+            .offline_player
+            .is_running
+            .store(true, Ordering::Relaxed);
+        Ok(Self {
+            sender,
+            state: state.clone(),
+        })
+    }
+
+    pub fn send(&self, request: OfflinePlayerRequest) -> Result<()> {
+        self.sender
+            .send(request)
+            .map_err(|_| anyhow::anyhow!("the offline player has stopped"))
+    }
+}
+
+impl Drop for OfflinePlayer {
+    fn drop(&mut self) {
+        let state = &self.state.offline_player;
+        state.is_running.store(false, Ordering::Relaxed);
+        state.is_playing.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The offline player's playing state, which is owned by the player's thread
+struct Player {
+    queue: Vec<PinnedTrack>,
+    index: usize,
+    decoder: Option<SymphoniaDecoder>,
+    is_playing: bool,
+    /// the gain of the volume fade in progress, from `0.0` to `1.0`
+    fade_gain: f64,
+    /// the change of the fade's gain for each audio sample
+    fade_step: f64,
+    /// the request handled once the volume is faded out
+    after_fade_out: Option<OfflinePlayerRequest>,
+}
+
+impl Player {
+    /// Handle a request, fading out the volume before pausing or skipping a playing track
+    fn handle_request(&mut self, request: OfflinePlayerRequest, state: &State) {
+        // a new request interrupts the fade-out in progress, whose request is handled right away
+        if let Some(request) = self.after_fade_out.take() {
+            self.apply(request, state);
+        }
+
+        let fade_out_ms = config::get_config().app_config.volume_fade_out_ms;
+        match request {
+            OfflinePlayerRequest::Pause
+            | OfflinePlayerRequest::ResumePause
+            | OfflinePlayerRequest::NextTrack
+            | OfflinePlayerRequest::PreviousTrack
+                if self.is_playing && self.decoder.is_some() && fade_out_ms > 0 =>
+            {
+                self.fade_step = -fade_step(fade_out_ms);
+                self.after_fade_out = Some(request);
+            }
+            request => self.apply(request, state),
+        }
+    }
+
+    /// Apply a request right away, fading in the volume after resuming or skipping a playing track
+    fn apply(&mut self, request: OfflinePlayerRequest, state: &State) {
+        let was_playing = self.is_playing;
+        let fade_in = match request {
+            OfflinePlayerRequest::Play(tracks, start) => {
+                self.queue = tracks;
+                self.index = start;
+                self.decoder = None;
+                self.is_playing = true;
+                false
+            }
+            OfflinePlayerRequest::ResumePause => {
+                self.is_playing = !self.is_playing;
+                !was_playing
+            }
+            OfflinePlayerRequest::Resume => {
+                self.is_playing = true;
+                !was_playing
+            }
+            OfflinePlayerRequest::Pause => {
+                self.is_playing = false;
+                false
+            }
+            OfflinePlayerRequest::NextTrack => {
+                self.index += 1;
+                self.decoder = None;
+                was_playing
+            }
+            OfflinePlayerRequest::PreviousTrack => {
+                self.index = self.index.saturating_sub(1);
+                self.decoder = None;
+                was_playing
+            }
+            OfflinePlayerRequest::Volume(volume) => {
+                state
+                    .offline_player
+                    .volume
+                    .store(volume.min(100), Ordering::Relaxed);
+                return;
+            }
+        };
+
+        let fade_in_ms = config::get_config().app_config.volume_fade_in_ms;
+        if fade_in && fade_in_ms > 0 {
+            self.fade_gain = 0.0;
+            self.fade_step = fade_step(fade_in_ms);
+        } else {
+            self.fade_gain = 1.0;
+            self.fade_step = 0.0;
+        }
+    }
+
+    /// Apply the volume and the fade's gain to decoded audio samples
+    fn apply_gain(&mut self, samples: &mut [f64], state: &State) {
+        let volume = f64::from(state.offline_player.volume.load(Ordering::Relaxed)) / 100.0;
+        for s in samples {
+            self.fade_gain = (self.fade_gain + self.fade_step).clamp(0.0, 1.0);
+            *s *= volume * self.fade_gain;
+        }
+        if self.fade_gain >= 1.0 && self.fade_step > 0.0 {
+            self.fade_step = 0.0;
+        }
+    }
+}
+
+/// Get the change of a fade's gain for each audio sample, for a fade lasting `ms` milliseconds
+fn fade_step(ms: u64) -> f64 {
+    let samples = ms as f64 * f64::from(SAMPLE_RATE) * f64::from(NUM_CHANNELS) / 1000.0;
+    1.0 / samples.max(1.0)
+}
+
+/// Run the offline player's loop, which decodes the queued tracks and writes
+/// their audio to the sink while handling the player's requests
+fn run(mut sink: Box<dyn Sink>, receiver: &mpsc::Receiver<OfflinePlayerRequest>, state: &State) {
+    let audio_folder = config::get_config().cache_folder.join("audio");
+    let mut converter = Converter::new(None);
+
+    let mut player = Player {
+        queue: vec![],
+        index: 0,
+        decoder: None,
+        is_playing: false,
+        fade_gain: 1.0,
+        fade_step: 0.0,
+        after_fade_out: None,
+    };
+    let mut is_sink_running = false;
+
+    loop {
+        // wait for a request when there is nothing to play
+        let request = if player.is_playing {
+            match receiver.try_recv() {
+                Ok(request) => Some(request),
+                Err(mpsc::TryRecvError::Empty) => None,
+                Err(mpsc::TryRecvError::Disconnected) => break,
+            }
+        } else {
+            match receiver.recv() {
+                Ok(request) => Some(request),
+                Err(mpsc::RecvError) => break,
+            }
+        };
+
+        if let Some(request) = request {
+            tracing::info!("Got an offline player request: {request:?}");
+            player.handle_request(request, state);
+        }
+
+        if player.is_playing && player.index >= player.queue.len() {
+            tracing::info!("Finished playing the offline queue");
+            player.is_playing = false;
+        }
+        if player.is_playing != is_sink_running {
+            let result = if player.is_playing {
+                sink.start()
+            } else {
+                sink.stop()
+            };
+            if let Err(err) = result {
+                tracing::warn!("Failed to change the audio sink's state: {err}");
+            }
+            is_sink_running = player.is_playing;
+            state
+                .offline_player
+                .is_playing
+                .store(is_sink_running, Ordering::Relaxed);
+        }
+        if !player.is_playing {
+            continue;
+        }
+
+        let Some(current) = player.decoder.as_mut() else {
+            let track = &player.queue[player.index];
+            match open_track(&audio_folder, track) {
+                Ok(d) => {
+                    tracing::info!("Playing \"{}\" from the audio cache", track.track.name);
+                    player.decoder = Some(d);
+                }
+                Err(err) => {
+                    tracing::warn!("Failed to play \"{}\" offline: {err:#}", track.track.name);
+                    player.index += 1;
+                }
+            }
+            continue;
+        };
+
+        match current.next_packet() {
+            Ok(Some((_, AudioPacket::Samples(mut samples)))) => {
+                player.apply_gain(&mut samples, state);
+                if let Err(err) = sink.write(AudioPacket::Samples(samples), &mut converter) {
+                    tracing::warn!("Failed to write audio to the sink: {err}");
+                    player.is_playing = false;
+                }
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                player.decoder = None;
+                player.index += 1;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to decode \"{}\": {err}",
+                    player.queue[player.index].track.name
+                );
+                player.decoder = None;
+                player.index += 1;
+            }
+        }
+
+        // the faded out request is handled once the volume is faded out
+        if player.fade_gain <= 0.0 {
+            if let Some(request) = player.after_fade_out.take() {
+                player.apply(request, state);
+            }
+        }
+    }
+
+    if is_sink_running {
+        sink.stop().unwrap_or_default();
+    }
+}
+
+/// Open a decoder of a pinned track's audio file in the audio cache
+fn open_track(audio_folder: &std::path::Path, track: &PinnedTrack) -> Result<SymphoniaDecoder> {
+    let path = crate::state::audio_cache_file_path(audio_folder, &track.file_id);
+    let file = std::fs::File::open(&path).with_context(|| format!("open {}", path.display()))?;
+
+    let mut key = [0; 16];
+    anyhow::ensure!(track.key.len() == 32, "invalid audio key");
+    for (i, b) in key.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&track.key[2 * i..2 * i + 2], 16).context("invalid audio key")?;
+    }
+
+    let mut data = vec![];
+    AudioDecrypt::new(Some(AudioKey(key)), file)
+        .read_to_end(&mut data)
+        .context("decrypt the audio file")?;
+
+    // the decoder only distinguishes Ogg Vorbis files from MP3 files
+    let format = if track.is_ogg_vorbis {
+        data.drain(..SPOTIFY_OGG_HEADER_END.min(data.len()));
+        AudioFileFormat::OGG_VORBIS_320
+    } else {
+        AudioFileFormat::MP3_320
+    };
+    SymphoniaDecoder::new(std::io::Cursor::new(data), format).context("open the audio decoder")
+}
@@ -1,5 +1,5 @@
 use crate::state::{
-    AlbumId, Category, ContextId, Item, ItemId, PlayableId, Playback, PlaylistId, TrackId,
+    AlbumId, Category, ContextId, Item, ItemId, PlayableId, Playback, PlaylistId, Track, TrackId,
 };
 
 #[derive(Clone, Debug)]
@@ -41,9 +41,11 @@ pub enum ClientRequest {
     },
     Search(String),
     AddPlayableToQueue(PlayableId<'static>),
+    /// add playable items to the queue in order
+    AddPlayablesToQueue(Vec<PlayableId<'static>>),
     AddAlbumToQueue(AlbumId<'static>),
-    AddPlayableToPlaylist(PlaylistId<'static>, PlayableId<'static>),
-    DeleteTrackFromPlaylist(PlaylistId<'static>, TrackId<'static>),
+    AddPlayablesToPlaylist(PlaylistId<'static>, Vec<PlayableId<'static>>),
+    DeleteTracksFromPlaylist(PlaylistId<'static>, Vec<TrackId<'static>>),
     ReorderPlaylistItems {
         playlist_id: PlaylistId<'static>,
         insert_index: usize,
@@ -51,15 +53,30 @@ pub enum ClientRequest {
         range_length: Option<usize>,
         snapshot_id: Option<String>,
     },
+    /// move ranges `(start, length)` of a playlist's items up or down by one position,
+    /// one range after another
+    MovePlaylistItemRanges {
+        playlist_id: PlaylistId<'static>,
+        ranges: Vec<(usize, usize)>,
+        up: bool,
+    },
+    AddTracksToLiked(Vec<Track>),
+    DeleteTracksFromLiked(Vec<TrackId<'static>>),
     AddToLibrary(Item),
     DeleteFromLibrary(ItemId),
     Player(PlayerRequest),
+    /// transfer the playback to a device specified by its name
+    ConnectDevice(String),
     GetCurrentUserQueue,
     GetLyrics {
-        track_id: TrackId<'static>,
+        track: Track,
     },
     #[cfg(feature = "streaming")]
     RestartIntegratedClient,
+    #[cfg(feature = "streaming")]
+    PinContext(ContextId),
+    #[cfg(feature = "streaming")]
+    UnpinContext(String),
     CreatePlaylist {
         playlist_name: String,
         public: bool,
@@ -0,0 +1,139 @@
+//! A scheduler of playback actions: the sleep timer and alarms.
+//!
+//! The scheduler checks its scheduled actions every second and
+//! handles them by sending player requests to the client.
+
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::config::Alarm;
+use crate::state::{ContextId, Playback, SharedState};
+
+use super::{ClientRequest, PlayerRequest};
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(1);
+
+/// starts the scheduler of the sleep timer and alarms
+pub async fn start_scheduler(state: SharedState, client_pub: flume::Sender<ClientRequest>) {
+    let mut last_checked = chrono::Local::now().naive_local();
+
+    loop {
+        tokio::time::sleep(SCHEDULER_TICK).await;
+
+        if let Err(err) = handle_sleep_timer(&state, &client_pub) {
+            tracing::error!("Failed to handle the sleep timer: {err:#}");
+        }
+
+        let now = chrono::Local::now().naive_local();
+        handle_alarms(&state, &client_pub, last_checked, now);
+        last_checked = now;
+    }
+}
+
+fn handle_sleep_timer(
+    state: &SharedState,
+    client_pub: &flume::Sender<ClientRequest>,
+) -> Result<()> {
+    let (remaining, volume) = {
+        let player = state.player.read();
+        let Some(timer) = player.sleep_timer.as_ref() else {
+            return Ok(());
+        };
+        let Some(remaining) = timer.remaining(&player, &state.data.read()) else {
+            return Ok(());
+        };
+        // the playing playback's volume, or the playing offline player's volume
+        let volume = player
+            .buffered_playback
+            .as_ref()
+            .filter(|p| p.is_playing)
+            .map(|p| p.volume.unwrap_or_default() as u8);
+        #[cfg(feature = "streaming")]
+        let volume = volume.or_else(|| state.offline_player.playing_volume());
+        (remaining, volume)
+    };
+
+    // the timer ends before the next check
+    if remaining < SCHEDULER_TICK {
+        if volume.is_some() {
+            tracing::info!("The sleep timer ended, pausing the playback");
+            client_pub.send(ClientRequest::Player(PlayerRequest::Pause))?;
+        }
+
+        let timer = state.player.write().sleep_timer.take();
+        if let Some(volume) = timer.and_then(|t| t.volume_before_fade_out) {
+            // restore the faded out volume after the playback is paused
+            let client_pub = client_pub.clone();
+            tokio::task::spawn(async move {
+                tokio::time::sleep(SCHEDULER_TICK).await;
+                client_pub
+                    .send_async(ClientRequest::Player(PlayerRequest::Volume(volume)))
+                    .await
+                    .unwrap_or_default();
+            });
+        }
+        return Ok(());
+    }
+
+    if let Some(volume) = volume {
+        let mut player = state.player.write();
+        // the timer may have been cancelled since the remaining time was computed
+        let Some(timer) = player.sleep_timer.as_mut() else {
+            return Ok(());
+        };
+        if remaining < timer.fade_out {
+            // linearly fade out the volume from its value when starting fading out
+            let initial_volume = *timer.volume_before_fade_out.get_or_insert(volume);
+            let new_volume = (f64::from(initial_volume) * remaining.as_secs_f64()
+                / timer.fade_out.as_secs_f64())
+            .round() as u8;
+            if new_volume < volume {
+                client_pub.send(ClientRequest::Player(PlayerRequest::Volume(new_volume)))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Start the playback of the alarms going off since the last check.
+/// A failing alarm is logged without affecting the other alarms.
+fn handle_alarms(
+    state: &SharedState,
+    client_pub: &flume::Sender<ClientRequest>,
+    last_checked: chrono::NaiveDateTime,
+    now: chrono::NaiveDateTime,
+) {
+    for alarm in &state.player.read().alarms {
+        if let Err(err) = handle_alarm(alarm, client_pub, last_checked, now) {
+            tracing::error!("Failed to handle the alarm at {}: {err:#}", alarm.time);
+        }
+    }
+}
+
+fn handle_alarm(
+    alarm: &Alarm,
+    client_pub: &flume::Sender<ClientRequest>,
+    last_checked: chrono::NaiveDateTime,
+    now: chrono::NaiveDateTime,
+) -> Result<()> {
+    let time = alarm.time_of_day()?;
+    // an alarm goes off if its time of day is passed since the last check
+    let alarm_time = now.date().and_time(time);
+    if !(last_checked < alarm_time && alarm_time <= now) {
+        return Ok(());
+    }
+
+    tracing::info!(
+        "Alarm at {} goes off, starting {}",
+        alarm.time,
+        alarm.context_uri
+    );
+    let id = ContextId::from_uri(&alarm.context_uri)?;
+    client_pub.send(ClientRequest::Player(PlayerRequest::StartPlayback(
+        Playback::Context(id, None),
+        Some(alarm.shuffle),
+    )))?;
+    Ok(())
+}
@@ -4,6 +4,9 @@ use crate::state::{
 };
 use serde::Deserialize;
 
+#[cfg(feature = "streaming")]
+use crate::state::Id;
+
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 /// Application's command
 pub enum Command {
@@ -47,6 +50,11 @@ pub enum Command {
 
     SwitchTheme,
     SwitchDevice,
+    #[cfg(feature = "streaming")]
+    SwitchDspPreset,
+    SleepTimer,
+    OpenCommandPalette,
+    OpenCommandLine,
     Search,
     Queue,
 
@@ -64,6 +72,8 @@ pub enum Command {
     RecentlyPlayedTrackPage,
     LikedTrackPage,
     LyricsPage,
+    #[cfg(feature = "streaming")]
+    VisualizerPage,
     LibraryPage,
     SearchPage,
     BrowsePage,
@@ -83,10 +93,19 @@ pub enum Command {
     MovePlaylistItemUp,
     MovePlaylistItemDown,
 
+    ToggleMarkSelectedTrack,
+    ToggleVisualSelection,
+    ClearTrackMarks,
+
     CreatePlaylist,
+
+    ExportLyrics,
+    IncreaseLyricsOffset,
+    DecreaseLyricsOffset,
+    FollowPlayingLyricsLine,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
 pub enum Action {
     GoToArtist,
     GoToAlbum,
@@ -106,11 +125,15 @@ pub enum Action {
     CopyLink,
     Follow,
     Unfollow,
+    PinForOffline,
+    UnpinFromOffline,
 }
 
 #[derive(Debug)]
 pub enum ActionContext {
     Track(Track),
+    /// marked tracks of a track table
+    Tracks(Vec<Track>),
     Album(Album),
     Artist(Artist),
     Playlist(Playlist),
@@ -128,11 +151,32 @@ pub enum ActionTarget {
     SelectedItem,
 }
 
+#[derive(Clone, Copy, Debug)]
 pub enum CommandOrAction {
     Command(Command),
     Action(Action, ActionTarget),
 }
 
+#[derive(Clone, Debug, Deserialize)]
+/// A step of a macro, which is run when the macro's key sequence is pressed
+pub enum MacroStep {
+    Command(Command),
+    /// an action on the selected item
+    Action(Action),
+    /// an action on the currently playing track
+    PlayingTrackAction(Action),
+    /// add the selected item (or the playing track) to a playlist specified by its ID or name
+    AddToPlaylist {
+        playlist: String,
+        #[serde(default)]
+        target: ActionTarget,
+    },
+    /// transfer the playback to a device specified by its name
+    SwitchDevice {
+        device: String,
+    },
+}
+
 impl From<Track> for ActionContext {
     fn from(v: Track) -> Self {
         Self::Track(v)
@@ -182,6 +226,7 @@ impl ActionContext {
     pub fn get_available_actions(&self, data: &DataReadGuard) -> Vec<Action> {
         match self {
             Self::Track(track) => construct_track_actions(track, data),
+            Self::Tracks(tracks) => construct_tracks_actions(tracks, data),
             Self::Album(album) => construct_album_actions(album, data),
             Self::Artist(artist) => construct_artist_actions(artist, data),
             Self::Playlist(playlist) => construct_playlist_actions(playlist, data),
@@ -215,6 +260,24 @@ pub fn construct_track_actions(track: &Track, data: &DataReadGuard) -> Vec<Actio
     actions
 }
 
+/// constructs a list of actions on multiple tracks
+pub fn construct_tracks_actions(tracks: &[Track], data: &DataReadGuard) -> Vec<Action> {
+    let mut actions = vec![Action::AddToPlaylist, Action::AddToQueue];
+
+    let n_liked = tracks
+        .iter()
+        .filter(|t| data.user_data.is_liked_track(t))
+        .count();
+    if n_liked < tracks.len() {
+        actions.push(Action::AddToLiked);
+    }
+    if n_liked > 0 {
+        actions.push(Action::DeleteFromLiked);
+    }
+
+    actions
+}
+
 /// constructs a list of actions on an album
 pub fn construct_album_actions(album: &Album, data: &DataReadGuard) -> Vec<Action> {
     let mut actions = vec![
@@ -229,6 +292,8 @@ pub fn construct_album_actions(album: &Album, data: &DataReadGuard) -> Vec<Actio
     } else {
         actions.push(Action::AddToLibrary);
     }
+    #[cfg(feature = "streaming")]
+    actions.push(construct_pin_action(&album.id.uri(), data));
     actions
 }
 
@@ -263,9 +328,21 @@ pub fn construct_playlist_actions(playlist: &Playlist, data: &DataReadGuard) ->
     } else {
         actions.push(Action::AddToLibrary);
     }
+    #[cfg(feature = "streaming")]
+    actions.push(construct_pin_action(&playlist.id.uri(), data));
     actions
 }
 
+/// constructs the pinning action on a playlist or an album
+#[cfg(feature = "streaming")]
+fn construct_pin_action(uri: &str, data: &DataReadGuard) -> Action {
+    if data.user_data.pinned_contexts.contains_key(uri) {
+        Action::UnpinFromOffline
+    } else {
+        Action::PinForOffline
+    }
+}
+
 /// constructs a list of actions on a show
 pub fn construct_show_actions(show: &Show, data: &DataReadGuard) -> Vec<Action> {
     let mut actions = vec![Action::CopyLink];
@@ -288,6 +365,84 @@ pub fn construct_episode_actions(episode: &Episode, _data: &DataReadGuard) -> Ve
 }
 
 impl Command {
+    /// all commands that don't have parameters
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::NextTrack,
+            Self::PreviousTrack,
+            Self::ResumePause,
+            Self::PlayRandom,
+            Self::Repeat,
+            Self::ToggleFakeTrackRepeatMode,
+            Self::Shuffle,
+            Self::Mute,
+            Self::SeekForward,
+            Self::SeekBackward,
+            Self::Quit,
+            Self::OpenCommandHelp,
+            Self::ClosePopup,
+            Self::SelectNextOrScrollDown,
+            Self::SelectPreviousOrScrollUp,
+            Self::PageSelectNextOrScrollDown,
+            Self::PageSelectPreviousOrScrollUp,
+            Self::SelectFirstOrScrollToTop,
+            Self::SelectLastOrScrollToBottom,
+            Self::JumpToCurrentTrackInContext,
+            Self::ChooseSelected,
+            Self::RefreshPlayback,
+            #[cfg(feature = "streaming")]
+            Self::RestartIntegratedClient,
+            Self::FocusNextWindow,
+            Self::FocusPreviousWindow,
+            Self::SwitchTheme,
+            Self::SwitchDevice,
+            #[cfg(feature = "streaming")]
+            Self::SwitchDspPreset,
+            Self::SleepTimer,
+            Self::OpenCommandPalette,
+            Self::OpenCommandLine,
+            Self::Search,
+            Self::Queue,
+            Self::ShowActionsOnSelectedItem,
+            Self::ShowActionsOnCurrentTrack,
+            Self::AddSelectedItemToQueue,
+            Self::JumpToHighlightTrackInContext,
+            Self::BrowseUserPlaylists,
+            Self::BrowseUserFollowedArtists,
+            Self::BrowseUserSavedAlbums,
+            Self::CurrentlyPlayingContextPage,
+            Self::TopTrackPage,
+            Self::RecentlyPlayedTrackPage,
+            Self::LikedTrackPage,
+            Self::LyricsPage,
+            #[cfg(feature = "streaming")]
+            Self::VisualizerPage,
+            Self::LibraryPage,
+            Self::SearchPage,
+            Self::BrowsePage,
+            Self::PreviousPage,
+            Self::OpenSpotifyLinkFromClipboard,
+            Self::SortTrackByTitle,
+            Self::SortTrackByArtists,
+            Self::SortTrackByAlbum,
+            Self::SortTrackByDuration,
+            Self::SortTrackByAddedDate,
+            Self::ReverseTrackOrder,
+            Self::SortLibraryAlphabetically,
+            Self::SortLibraryByRecent,
+            Self::MovePlaylistItemUp,
+            Self::MovePlaylistItemDown,
+            Self::ToggleMarkSelectedTrack,
+            Self::ToggleVisualSelection,
+            Self::ClearTrackMarks,
+            Self::CreatePlaylist,
+            Self::ExportLyrics,
+            Self::IncreaseLyricsOffset,
+            Self::DecreaseLyricsOffset,
+            Self::FollowPlayingLyricsLine,
+        ]
+    }
+
     pub fn desc(self) -> String {
         if let Self::VolumeChange { offset } = self {
             return format!("change playback volume by {offset}");
@@ -336,6 +491,13 @@ impl Command {
             Self::FocusPreviousWindow => "focus the previous focusable window (if any)",
             Self::SwitchTheme => "open a popup for switching theme",
             Self::SwitchDevice => "open a popup for switching device",
+            #[cfg(feature = "streaming")]
+            Self::SwitchDspPreset => "open a popup for switching the integrated player's DSP preset",
+            Self::SleepTimer => "open a popup for setting the sleep timer",
+            Self::OpenCommandPalette => {
+                "open a popup for searching and running commands and actions on the selected item"
+            }
+            Self::OpenCommandLine => "open a command line for running commands with arguments",
             Self::Search => "open a popup for searching in the current page",
             Self::BrowseUserPlaylists => "open a popup for browsing user's playlists",
             Self::BrowseUserFollowedArtists => "open a popup for browsing user's followed artists",
@@ -345,6 +507,8 @@ impl Command {
             Self::RecentlyPlayedTrackPage => "go to the user recently played track page",
             Self::LikedTrackPage => "go to the user liked track page",
             Self::LyricsPage => "go to the lyrics page of the current track",
+            #[cfg(feature = "streaming")]
+            Self::VisualizerPage => "go to the audio visualizer page of the integrated player",
             Self::LibraryPage => "go to the user library page",
             Self::SearchPage => "go to the search page",
             Self::BrowsePage => "go to the browse page",
@@ -364,9 +528,57 @@ impl Command {
             }
             Self::MovePlaylistItemUp => "move playlist item up one position",
             Self::MovePlaylistItemDown => "move playlist item down one position",
+            Self::ToggleMarkSelectedTrack => "toggle the mark of the selected track",
+            Self::ToggleVisualSelection => {
+                "start a visual selection of tracks or mark the selected tracks"
+            }
+            Self::ClearTrackMarks => "clear the marks and the visual selection of tracks",
             Self::CreatePlaylist => "create a new playlist",
+            Self::ExportLyrics => "export the lyrics shown in the lyrics page to an `.lrc` file",
+            Self::IncreaseLyricsOffset => {
+                "show the lyrics of the current track earlier (increase its timing offset)"
+            }
+            Self::DecreaseLyricsOffset => {
+                "show the lyrics of the current track later (decrease its timing offset)"
+            }
+            Self::FollowPlayingLyricsLine => {
+                "clear the lyrics page's cursor to follow the currently playing line"
+            }
             Self::VolumeChange { offset: _ } => unreachable!(),
         }
         .to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Command;
+
+    #[test]
+    fn all_commands_listed() {
+        // list the `Command` variants from its definition, so that a new command
+        // without parameters can't be left out of `Command::all`
+        let source = include_str!("command.rs");
+        let start = source.find("pub enum Command {").unwrap();
+        let end = start + source[start..].find("\n}").unwrap();
+        let definition = source[start..end].lines().skip(1).map(str::trim);
+
+        let all = Command::all();
+        let mut is_cfg = false;
+        for line in definition {
+            let Some(name) = line.strip_suffix(',') else {
+                is_cfg = line.starts_with("#[cfg(");
+                continue;
+            };
+            if !name.starts_with(char::is_uppercase) || name == "None" {
+                continue;
+            }
+            match serde_json::from_str::<Command>(&format!("\"{name}\"")) {
+                Ok(command) => assert!(all.contains(&command), "{name} is missing in all()"),
+                // the variant is disabled by a feature
+                Err(_) => assert!(is_cfg, "{name} is not a command"),
+            }
+            is_cfg = false;
+        }
+    }
+}
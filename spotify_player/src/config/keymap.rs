@@ -1,9 +1,9 @@
 use crate::{
-    command::{Action, ActionTarget, Command, CommandOrAction},
+    command::{Action, ActionTarget, Command, CommandOrAction, MacroStep},
     key::{Key, KeySequence},
 };
-use anyhow::Result;
-use serde::Deserialize;
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Deserializer};
 
 #[derive(Debug, Deserialize)]
 /// Application's keymap configurations
@@ -12,6 +12,8 @@ pub struct KeymapConfig {
     pub keymaps: Vec<Keymap>,
     #[serde(default)]
     pub actions: Vec<ActionMap>,
+    #[serde(default)]
+    pub macros: Vec<MacroMap>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -30,10 +32,32 @@ pub struct ActionMap {
     pub action: Action,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+/// A keymap that runs a list of commands and actions in order when a key sequence is pressed
+pub struct MacroMap {
+    pub key_sequence: KeySequence,
+    #[serde(deserialize_with = "deserialize_macro_steps")]
+    pub steps: Vec<MacroStep>,
+}
+
+fn deserialize_macro_steps<'de, D>(deserializer: D) -> Result<Vec<MacroStep>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let steps = Vec::<MacroStep>::deserialize(deserializer)?;
+    if steps.is_empty() {
+        return Err(serde::de::Error::custom(
+            "a macro requires at least one step",
+        ));
+    }
+    Ok(steps)
+}
+
 impl Default for KeymapConfig {
     fn default() -> Self {
         KeymapConfig {
             actions: vec![],
+            macros: vec![],
             keymaps: vec![
                 Keymap {
                     key_sequence: "n".into(),
@@ -144,6 +168,23 @@ impl Default for KeymapConfig {
                     key_sequence: "D".into(),
                     command: Command::SwitchDevice,
                 },
+                #[cfg(feature = "streaming")]
+                Keymap {
+                    key_sequence: "e".into(),
+                    command: Command::SwitchDspPreset,
+                },
+                Keymap {
+                    key_sequence: "S".into(),
+                    command: Command::SleepTimer,
+                },
+                Keymap {
+                    key_sequence: "M-x".into(),
+                    command: Command::OpenCommandPalette,
+                },
+                Keymap {
+                    key_sequence: ":".into(),
+                    command: Command::OpenCommandLine,
+                },
                 Keymap {
                     key_sequence: "u p".into(),
                     command: Command::BrowseUserPlaylists,
@@ -180,6 +221,11 @@ impl Default for KeymapConfig {
                     key_sequence: "l".into(),
                     command: Command::LyricsPage,
                 },
+                #[cfg(feature = "streaming")]
+                Keymap {
+                    key_sequence: "g v".into(),
+                    command: Command::VisualizerPage,
+                },
                 Keymap {
                     key_sequence: "g l".into(),
                     command: Command::LibraryPage,
@@ -320,6 +366,18 @@ impl Default for KeymapConfig {
                     key_sequence: "C-j".into(),
                     command: Command::MovePlaylistItemDown,
                 },
+                Keymap {
+                    key_sequence: "m".into(),
+                    command: Command::ToggleMarkSelectedTrack,
+                },
+                Keymap {
+                    key_sequence: "v".into(),
+                    command: Command::ToggleVisualSelection,
+                },
+                Keymap {
+                    key_sequence: "M".into(),
+                    command: Command::ClearTrackMarks,
+                },
                 Keymap {
                     key_sequence: "N".into(),
                     command: Command::CreatePlaylist,
@@ -328,6 +386,22 @@ impl Default for KeymapConfig {
                     key_sequence: "g c".into(),
                     command: Command::JumpToCurrentTrackInContext,
                 },
+                Keymap {
+                    key_sequence: "E".into(),
+                    command: Command::ExportLyrics,
+                },
+                Keymap {
+                    key_sequence: "]".into(),
+                    command: Command::IncreaseLyricsOffset,
+                },
+                Keymap {
+                    key_sequence: "[".into(),
+                    command: Command::DecreaseLyricsOffset,
+                },
+                Keymap {
+                    key_sequence: "=".into(),
+                    command: Command::FollowPlayingLyricsLine,
+                },
             ],
         }
     }
@@ -351,13 +425,26 @@ impl KeymapConfig {
                 );
             }
             Ok(content) => {
-                let mut parsed = toml::from_str::<Self>(&content)?;
+                // an invalid entry (e.g. an unknown command) is reported with its line in the file
+                let mut parsed = toml::from_str::<Self>(&content).with_context(|| {
+                    format!(
+                        "failed to parse the keymap config file (path={})",
+                        file_path.display()
+                    )
+                })?;
                 std::mem::swap(&mut self.keymaps, &mut parsed.keymaps);
                 std::mem::swap(&mut self.actions, &mut parsed.actions);
+                self.macros = parsed.macros;
 
                 // a dumb approach (with quadratic complexity) to merge two different keymap arrays
                 // while keeping the invariant:
                 // - each `KeySequence` is mapped to only one `Command`.
+                // Default keymaps are also overridden by user's macros with the same key sequence.
+                let is_macro_key = |key_sequence: &KeySequence| {
+                    self.macros.iter().any(|m| m.key_sequence == *key_sequence)
+                };
+                parsed.keymaps.retain(|k| !is_macro_key(&k.key_sequence));
+                parsed.actions.retain(|a| !is_macro_key(&a.key_sequence));
                 parsed.keymaps.into_iter().for_each(|keymap| {
                     if !self
                         .keymaps
@@ -397,11 +484,34 @@ impl KeymapConfig {
             .collect()
     }
 
-    /// checks if there is any command or action that has a given `prefix` key sequence as its prefix
+    /// checks if there is any command, action or macro that has a given `prefix` key sequence as its prefix
     pub fn has_matched_prefix(&self, prefix: &KeySequence) -> bool {
         let keymaps = self.find_matched_prefix_keymaps(prefix);
         let actions = self.find_matched_prefix_actions(prefix);
-        !keymaps.is_empty() || !actions.is_empty()
+        !keymaps.is_empty()
+            || !actions.is_empty()
+            || self
+                .macros
+                .iter()
+                .any(|m| prefix.is_prefix(&m.key_sequence))
+    }
+
+    /// finds all key sequences mapped to a command
+    pub fn find_key_sequences_from_command(&self, command: Command) -> Vec<&KeySequence> {
+        self.keymaps
+            .iter()
+            .filter(|&keymap| keymap.command == command)
+            .map(|keymap| &keymap.key_sequence)
+            .collect()
+    }
+
+    /// finds all key sequences mapped to an action on the selected item
+    pub fn find_key_sequences_from_action(&self, action: Action) -> Vec<&KeySequence> {
+        self.actions
+            .iter()
+            .filter(|&a| a.action == action && a.target == ActionTarget::SelectedItem)
+            .map(|a| &a.key_sequence)
+            .collect()
     }
 
     /// finds a command from a mapped key sequence
@@ -423,6 +533,14 @@ impl KeymapConfig {
             .map(|action| (action.action, action.target))
     }
 
+    /// finds a macro's steps from a mapped key sequence
+    pub fn find_macro_from_key_sequence(&self, key_sequence: &KeySequence) -> Option<&[MacroStep]> {
+        self.macros
+            .iter()
+            .find(|m| m.key_sequence == *key_sequence)
+            .map(|m| m.steps.as_slice())
+    }
+
     /// finds a command or action from a mapped key sequence
     pub fn find_command_or_action_from_key_sequence(
         &self,
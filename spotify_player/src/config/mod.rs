@@ -54,6 +54,8 @@ pub struct AppConfig {
     pub client_id_command: Option<Command>,
 
     pub client_port: u16,
+    pub http_api_address: Option<String>,
+    pub http_api_token: Option<String>,
 
     pub login_redirect_uri: String,
 
@@ -111,12 +113,26 @@ pub struct AppConfig {
 
     pub device: DeviceConfig,
 
+    pub dsp_presets: Vec<DspPreset>,
+
     #[cfg(all(feature = "streaming", feature = "notify"))]
     pub notify_streaming_only: bool,
 
     pub seek_duration_secs: u16,
 
     pub sort_artist_albums_by_type: bool,
+
+    pub lyrics_providers: Vec<LyricsProvider>,
+    pub lrclib_base_url: String,
+    pub lyrics_folder: Option<PathBuf>,
+    pub lyrics_offset_step_ms: u16,
+    pub lyrics_translation_command: Option<Command>,
+
+    pub sleep_timer_fade_out_secs: u64,
+    pub alarms: Vec<Alarm>,
+
+    pub volume_fade_out_ms: u64,
+    pub volume_fade_in_ms: u64,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -143,6 +159,31 @@ pub enum ProgressBarType {
 }
 config_parser_impl!(ProgressBarType);
 
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// A source of track lyrics
+pub enum LyricsProvider {
+    /// Local `.lrc` files in the `lyrics_folder`
+    Local,
+    /// Spotify's lyrics service (synced lyrics if available)
+    Spotify,
+    /// An LRCLIB-compatible lyrics API (synced lyrics if available)
+    Lrclib,
+    /// The Genius website (unsynced lyrics)
+    Genius,
+}
+config_parser_impl!(LyricsProvider);
+
+impl std::fmt::Display for LyricsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local => write!(f, "local file"),
+            Self::Spotify => write!(f, "Spotify"),
+            Self::Lrclib => write!(f, "LRCLIB"),
+            Self::Genius => write!(f, "Genius"),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
 pub struct Command {
     pub command: String,
@@ -153,11 +194,22 @@ pub struct Command {
 impl Command {
     /// Execute a command, returning stdout if succeeded or stderr if failed
     pub fn execute(&self, extra_args: Option<Vec<String>>) -> anyhow::Result<String> {
+        self.execute_with_envs(extra_args, &[])
+    }
+
+    /// Execute a command with additional environment variables,
+    /// returning stdout if succeeded or stderr if failed
+    pub fn execute_with_envs(
+        &self,
+        extra_args: Option<Vec<String>>,
+        envs: &[(&str, &str)],
+    ) -> anyhow::Result<String> {
         let mut args = self.args.clone();
         args.extend(extra_args.unwrap_or_default());
 
         let output = std::process::Command::new(&self.command)
             .args(&args)
+            .envs(envs.iter().copied())
             .output()?;
 
         if !output.status.success() {
@@ -168,9 +220,40 @@ impl Command {
         let stdout = std::str::from_utf8(&output.stdout)?.to_string();
         Ok(stdout)
     }
+
+    /// Execute a command with the given `input` written to its stdin,
+    /// returning stdout if succeeded or stderr if failed
+    pub fn execute_with_stdin(&self, input: String) -> anyhow::Result<String> {
+        let mut child = std::process::Command::new(&self.command)
+            .args(&self.args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()?;
+
+        // write the input in a separate thread to avoid blocking on a full stdout pipe
+        let mut stdin = child.stdin.take().expect("stdin should be piped");
+        let writer = std::thread::spawn(move || {
+            use std::io::Write;
+            stdin.write_all(input.as_bytes())
+        });
+        let output = child.wait_with_output()?;
+        writer
+            .join()
+            .map_err(|_| anyhow::anyhow!("failed to write to the command's stdin"))??;
+
+        if !output.status.success() {
+            let stderr = std::str::from_utf8(&output.stderr)?.to_string();
+            anyhow::bail!(stderr);
+        }
+
+        let stdout = std::str::from_utf8(&output.stdout)?.to_string();
+        Ok(stdout)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 /// Application device configurations
 pub struct DeviceConfig {
     pub name: String,
@@ -178,8 +261,92 @@ pub struct DeviceConfig {
     pub volume: u8,
     pub bitrate: u16,
     pub audio_cache: bool,
+    pub audio_cache_size_limit_mb: Option<u64>,
     pub normalization: bool,
+    pub normalization_type: String,
+    pub normalization_method: String,
+    pub normalization_pregain_db: f64,
+    pub normalization_threshold_dbfs: f64,
+    pub normalization_attack_ms: u64,
+    pub normalization_release_ms: u64,
+    pub normalization_knee_db: f64,
+    pub gapless: bool,
     pub autoplay: bool,
+    pub backend: Option<String>,
+    pub output_device: Option<String>,
+    pub audio_format: String,
+    pub mixer: String,
+    pub mixer_device: String,
+    pub mixer_control: String,
+    pub volume_ctrl: String,
+    pub dsp_preset: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+/// A named preset of the integrated player's DSP (digital signal processing) stage
+pub struct DspPreset {
+    pub name: String,
+    #[serde(default)]
+    pub preamp_db: f64,
+    /// gains (in dB) of the 10-band graphic equalizer's bands
+    #[serde(default)]
+    pub graphic_eq: Vec<f64>,
+    /// bands of the parametric equalizer
+    #[serde(default)]
+    pub eq_bands: Vec<EqBand>,
+    #[serde(default)]
+    pub mono: bool,
+    /// channel balance, from `-1.0` (left only) to `1.0` (right only)
+    #[serde(default)]
+    pub balance: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+/// A band of the parametric equalizer
+pub struct EqBand {
+    #[serde(default)]
+    pub kind: EqBandKind,
+    pub frequency: f64,
+    pub gain_db: f64,
+    #[serde(default = "default_eq_band_q")]
+    pub q: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default)]
+pub enum EqBandKind {
+    #[default]
+    Peaking,
+    LowShelf,
+    HighShelf,
+}
+
+fn default_eq_band_q() -> f64 {
+    1.0
+}
+
+/// the maximum absolute gain (in dB) of a DSP preset's preamp and equalizer bands
+const MAX_DSP_GAIN_DB: f64 = 24.0;
+
+fn is_valid_dsp_gain(gain_db: f64) -> bool {
+    (-MAX_DSP_GAIN_DB..=MAX_DSP_GAIN_DB).contains(&gain_db)
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+/// An alarm starting a context's playback at a time of day
+pub struct Alarm {
+    /// the alarm's time of day in the `HH:MM` format
+    pub time: String,
+    pub context_uri: String,
+    #[serde(default)]
+    pub shuffle: bool,
+}
+
+impl Alarm {
+    /// Parse the alarm's time of day
+    pub fn time_of_day(&self) -> Result<chrono::NaiveTime> {
+        chrono::NaiveTime::parse_from_str(&self.time, "%H:%M")
+            .map_err(|err| anyhow!("invalid alarm time {}: {err}", self.time))
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
@@ -261,6 +428,8 @@ impl Default for AppConfig {
             client_id_command: None,
 
             client_port: 8080,
+            http_api_address: None,
+            http_api_token: None,
 
             login_redirect_uri: "http://127.0.0.1:8989/login".to_string(),
 
@@ -274,6 +443,9 @@ impl Default for AppConfig {
                 "shuffle".to_string(),
                 "volume".to_string(),
                 "device".to_string(),
+                "normalization".to_string(),
+                "dsp_preset".to_string(),
+                "sleep_timer".to_string(),
             ],
             #[cfg(feature = "notify")]
             notify_format: NotifyFormat {
@@ -331,12 +503,31 @@ impl Default for AppConfig {
 
             device: DeviceConfig::default(),
 
+            dsp_presets: vec![],
+
             #[cfg(all(feature = "streaming", feature = "notify"))]
             notify_streaming_only: false,
 
             seek_duration_secs: 5,
 
             sort_artist_albums_by_type: false,
+
+            lyrics_providers: vec![
+                LyricsProvider::Local,
+                LyricsProvider::Spotify,
+                LyricsProvider::Lrclib,
+                LyricsProvider::Genius,
+            ],
+            lrclib_base_url: "https://lrclib.net".to_string(),
+            lyrics_folder: None,
+            lyrics_offset_step_ms: 100,
+            lyrics_translation_command: None,
+
+            sleep_timer_fade_out_secs: 0,
+            alarms: vec![],
+
+            volume_fade_out_ms: 0,
+            volume_fade_in_ms: 0,
         }
     }
 }
@@ -349,8 +540,25 @@ impl Default for DeviceConfig {
             volume: 70,
             bitrate: 320,
             audio_cache: false,
+            audio_cache_size_limit_mb: None,
             normalization: false,
+            normalization_type: "auto".to_string(),
+            normalization_method: "dynamic".to_string(),
+            normalization_pregain_db: 0.0,
+            normalization_threshold_dbfs: -2.0,
+            normalization_attack_ms: 5,
+            normalization_release_ms: 100,
+            normalization_knee_db: 5.0,
+            gapless: true,
             autoplay: false,
+            backend: None,
+            output_device: None,
+            audio_format: "S16".to_string(),
+            mixer: "softvol".to_string(),
+            mixer_device: "default".to_string(),
+            mixer_control: "PCM".to_string(),
+            volume_ctrl: "log".to_string(),
+            dsp_preset: None,
         }
     }
 }
@@ -385,9 +593,55 @@ impl AppConfig {
         }
 
         config.layout.check_values()?;
+        config.check_dsp_presets()?;
+        for alarm in &config.alarms {
+            alarm.time_of_day()?;
+        }
         Ok(config)
     }
 
+    /// Find a DSP preset by its name
+    pub fn find_dsp_preset(&self, name: &str) -> Option<&DspPreset> {
+        self.dsp_presets.iter().find(|p| p.name == name)
+    }
+
+    fn check_dsp_presets(&self) -> Result<()> {
+        for preset in &self.dsp_presets {
+            if !preset.graphic_eq.is_empty() && preset.graphic_eq.len() != 10 {
+                anyhow::bail!(
+                    "Invalid DSP preset {}: graphic_eq must have exactly 10 bands",
+                    preset.name
+                );
+            }
+            if !is_valid_dsp_gain(preset.preamp_db)
+                || !preset.graphic_eq.iter().copied().all(is_valid_dsp_gain)
+            {
+                anyhow::bail!(
+                    "Invalid DSP preset {}: gains must be between -{MAX_DSP_GAIN_DB} and {MAX_DSP_GAIN_DB} dB",
+                    preset.name
+                );
+            }
+            // a band's frequency must be below the Nyquist frequency of the integrated player's 44.1kHz audio
+            if let Some(band) = preset.eq_bands.iter().find(|b| {
+                b.frequency <= 0.0
+                    || b.frequency >= 22050.0
+                    || b.q <= 0.0
+                    || !is_valid_dsp_gain(b.gain_db)
+            }) {
+                anyhow::bail!(
+                    "Invalid DSP preset {}: invalid equalizer band {band:?}",
+                    preset.name
+                );
+            }
+        }
+        if let Some(ref name) = self.device.dsp_preset {
+            if self.find_dsp_preset(name).is_none() {
+                anyhow::bail!("Invalid device's dsp_preset: no DSP preset named {name}");
+            }
+        }
+        Ok(())
+    }
+
     // parses configurations from an application config file in `path` folder,
     // then updates the current configurations accordingly.
     // returns false if no config file found and true otherwise
@@ -77,9 +77,14 @@ struct ComponentStyle {
     table_header: Option<Style>,
     selection: Option<Style>,
     secondary_row: Option<Style>,
+    marked_row: Option<Style>,
     like: Option<Style>,
     lyrics_played: Option<Style>,
     lyrics_playing: Option<Style>,
+    #[cfg(feature = "streaming")]
+    visualizer_spectrum: Option<Style>,
+    #[cfg(feature = "streaming")]
+    visualizer_waveform: Option<Style>,
 }
 
 #[derive(Default, Clone, Debug, Deserialize)]
@@ -333,6 +338,14 @@ impl Theme {
             .style(&self.palette)
     }
 
+    pub fn marked_row(&self) -> style::Style {
+        self.component_style
+            .marked_row
+            .as_ref()
+            .unwrap_or(&Style::default().fg(StyleColor::Yellow))
+            .style(&self.palette)
+    }
+
     pub fn like(&self) -> style::Style {
         self.component_style
             .like
@@ -360,6 +373,24 @@ impl Theme {
             )
             .style(&self.palette)
     }
+
+    #[cfg(feature = "streaming")]
+    pub fn visualizer_spectrum(&self) -> style::Style {
+        self.component_style
+            .visualizer_spectrum
+            .as_ref()
+            .unwrap_or(&Style::default().fg(StyleColor::Green))
+            .style(&self.palette)
+    }
+
+    #[cfg(feature = "streaming")]
+    pub fn visualizer_waveform(&self) -> style::Style {
+        self.component_style
+            .visualizer_waveform
+            .as_ref()
+            .unwrap_or(&Style::default().fg(StyleColor::Cyan))
+            .style(&self.palette)
+    }
 }
 
 impl Style {
@@ -0,0 +1,377 @@
+//! A DSP (digital signal processing) stage of the integrated player.
+//!
+//! Audio samples decoded by `librespot` are processed based on the active DSP preset
+//! before being written to the audio backend's sink.
+
+use librespot_playback::{
+    audio_backend::{Sink, SinkResult},
+    convert::Converter,
+    decoder::AudioPacket,
+    SAMPLE_RATE,
+};
+
+use crate::config::{self, DspPreset, EqBand, EqBandKind};
+use crate::state::SharedState;
+
+/// center frequencies (in Hz) of the 10-band graphic equalizer's bands
+const GRAPHIC_EQ_FREQUENCIES: [f64; 10] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// Q factor of the graphic equalizer's bands, which corresponds to a one-octave bandwidth
+const GRAPHIC_EQ_Q: f64 = 1.41;
+
+/// the number of recent audio samples recorded for the visualizer page, must be a power of two
+pub const VISUALIZER_SAMPLES: usize = 2048;
+
+/// the frequency range (in Hz) of the visualizer page's spectrum
+const SPECTRUM_FREQUENCY_RANGE: (f64, f64) = (40.0, 16000.0);
+
+/// the minimum level (in dB) of the visualizer page's spectrum
+const SPECTRUM_MIN_DB: f64 = -60.0;
+
+/// A sink processing audio samples before writing them to the audio backend's sink.
+/// The processed samples are also recorded for the visualizer page.
+pub struct DspSink {
+    sink: Box<dyn Sink>,
+    state: SharedState,
+    /// the name of the preset that `dsp` is built from
+    preset: Option<String>,
+    dsp: Option<Dsp>,
+}
+
+impl DspSink {
+    pub fn new(sink: Box<dyn Sink>, state: SharedState) -> Self {
+        Self {
+            sink,
+            state,
+            preset: None,
+            dsp: None,
+        }
+    }
+
+    /// Rebuild the DSP processor if the active preset has changed
+    fn sync_preset(&mut self) {
+        let preset = self.state.dsp_preset.read();
+        if *preset == self.preset {
+            return;
+        }
+
+        self.dsp = preset.as_deref().and_then(|name| {
+            let p = config::get_config().app_config.find_dsp_preset(name);
+            if p.is_none() {
+                tracing::warn!("Unknown DSP preset: {name}");
+            }
+            p.map(Dsp::new)
+        });
+        self.preset.clone_from(&preset);
+        tracing::info!("Applied DSP preset: {:?}", self.preset);
+    }
+
+    /// Record the most recent samples, downmixed to mono, for the visualizer page
+    fn record_samples(&self, samples: &[f64]) {
+        let mut buffer = self.state.audio_samples.lock();
+        buffer.extend(
+            samples
+                .chunks_exact(2)
+                .map(|frame| f64::midpoint(frame[0], frame[1])),
+        );
+        let excess = buffer.len().saturating_sub(VISUALIZER_SAMPLES);
+        buffer.drain(..excess);
+    }
+}
+
+impl Sink for DspSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        self.sync_preset();
+        let packet = match (packet, self.dsp.as_mut()) {
+            (AudioPacket::Samples(mut samples), Some(dsp)) => {
+                dsp.process(&mut samples);
+                AudioPacket::Samples(samples)
+            }
+            (packet, _) => packet,
+        };
+        if let AudioPacket::Samples(ref samples) = packet {
+            self.record_samples(samples);
+        }
+        self.sink.write(packet, converter)
+    }
+}
+
+/// A DSP processor of interleaved stereo samples, which is built from a DSP preset
+struct Dsp {
+    preamp: f64,
+    /// equalizer filters of each band, one per channel
+    filters: Vec<[Biquad; 2]>,
+    mono: bool,
+    /// gains of the left and right channels
+    balance: [f64; 2],
+}
+
+impl Dsp {
+    fn new(preset: &DspPreset) -> Self {
+        let graphic_bands =
+            preset
+                .graphic_eq
+                .iter()
+                .zip(GRAPHIC_EQ_FREQUENCIES)
+                .map(|(&gain_db, frequency)| EqBand {
+                    kind: EqBandKind::Peaking,
+                    frequency,
+                    gain_db,
+                    q: GRAPHIC_EQ_Q,
+                });
+        let filters = graphic_bands
+            .chain(preset.eq_bands.iter().cloned())
+            // a band without any gain doesn't change the audio
+            .filter(|band| band.gain_db != 0.0)
+            .map(|band| {
+                let filter = Biquad::new(&band);
+                [filter.clone(), filter]
+            })
+            .collect();
+
+        let balance = preset.balance.clamp(-1.0, 1.0);
+        Self {
+            preamp: db_to_gain(preset.preamp_db),
+            filters,
+            mono: preset.mono,
+            balance: [(1.0 - balance).min(1.0), (1.0 + balance).min(1.0)],
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f64]) {
+        // `librespot` always decodes audio into interleaved stereo samples
+        for frame in samples.chunks_exact_mut(2) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample = self
+                    .filters
+                    .iter_mut()
+                    .fold(*sample * self.preamp, |x, filters| {
+                        filters[channel].process(x)
+                    });
+            }
+            if self.mono {
+                let mixed = f64::midpoint(frame[0], frame[1]);
+                frame[0] = mixed;
+                frame[1] = mixed;
+            }
+            for (sample, gain) in frame.iter_mut().zip(self.balance) {
+                // boosting the audio may make samples out of range
+                *sample = (*sample * gain).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+/// A second-order IIR filter implementing an equalizer band,
+/// using the formulae from the Audio EQ Cookbook
+#[derive(Clone)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn new(band: &EqBand) -> Self {
+        let a = 10_f64.powf(band.gain_db / 40.0);
+        let w0 = 2.0 * std::f64::consts::PI * band.frequency / f64::from(SAMPLE_RATE);
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * band.q);
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match band.kind {
+            EqBandKind::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            EqBandKind::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha),
+                (a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha,
+            ),
+            EqBandKind::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha),
+                (a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha,
+            ),
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    /// Process a sample (transposed direct form II)
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+fn db_to_gain(db: f64) -> f64 {
+    10_f64.powf(db / 20.0)
+}
+
+/// Compute levels (from `0.0` to `1.0`) of `n_bands` logarithmically spaced frequency bands
+/// of the given mono samples
+pub fn spectrum(samples: &[f64], n_bands: usize) -> Vec<f64> {
+    let n = VISUALIZER_SAMPLES;
+
+    // apply a Hann window to the most recent samples, padded with silence
+    let mut re = vec![0.0; n];
+    let mut im = vec![0.0; n];
+    let samples = &samples[samples.len().saturating_sub(n)..];
+    for (i, sample) in samples.iter().enumerate() {
+        let w = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / (n - 1) as f64).cos();
+        re[i] = sample * w;
+    }
+    fft(&mut re, &mut im);
+
+    let (min_frequency, max_frequency) = SPECTRUM_FREQUENCY_RANGE;
+    let bin_width = f64::from(SAMPLE_RATE) / n as f64;
+    let bin = |frequency: f64| ((frequency / bin_width).round() as usize).min(n / 2);
+    (0..n_bands)
+        .map(|band| {
+            let frequency = |band: usize| {
+                min_frequency * (max_frequency / min_frequency).powf(band as f64 / n_bands as f64)
+            };
+            let (start, end) = (bin(frequency(band)), bin(frequency(band + 1)));
+            let amplitude = (start..=end.max(start))
+                .map(|i| re[i].hypot(im[i]))
+                .fold(0.0, f64::max);
+            // a full-scale sine wave's amplitude is `n / 4` with the Hann window
+            let db = 20.0 * (amplitude / (n / 4) as f64).log10();
+            ((db - SPECTRUM_MIN_DB) / -SPECTRUM_MIN_DB).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+/// An in-place radix-2 FFT, whose input's length must be a power of two
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+
+    // bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f64::consts::PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{db_to_gain, fft, Biquad, EqBand, EqBandKind, VISUALIZER_SAMPLES};
+
+    fn band(kind: EqBandKind, gain_db: f64) -> EqBand {
+        EqBand {
+            kind,
+            frequency: 1000.0,
+            gain_db,
+            q: 1.0,
+        }
+    }
+
+    /// Get the filter's output after processing a constant (DC) input for a while
+    fn dc_response(band: &EqBand) -> f64 {
+        let mut filter = Biquad::new(band);
+        (0..10_000).fold(0.0, |_, _| filter.process(1.0))
+    }
+
+    #[test]
+    fn biquad_dc_gain() {
+        // a band without any gain passes the audio through
+        let mut filter = Biquad::new(&band(EqBandKind::Peaking, 0.0));
+        for x in [0.5, -0.25, 1.0, 0.0] {
+            assert!((filter.process(x) - x).abs() < 1e-12);
+        }
+
+        // peaking and high-shelf bands don't change the DC component
+        for kind in [EqBandKind::Peaking, EqBandKind::HighShelf] {
+            assert!((dc_response(&band(kind, 6.0)) - 1.0).abs() < 1e-6);
+        }
+        // a low-shelf band applies its gain to the DC component
+        let gain = dc_response(&band(EqBandKind::LowShelf, 6.0));
+        assert!((gain - db_to_gain(6.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fft_sine_bin() {
+        let n = VISUALIZER_SAMPLES;
+        let k = 64;
+        let mut re = (0..n)
+            .map(|i| (2.0 * std::f64::consts::PI * (k * i) as f64 / n as f64).sin())
+            .collect::<Vec<_>>();
+        let mut im = vec![0.0; n];
+        fft(&mut re, &mut im);
+
+        let magnitudes = (0..=n / 2).map(|i| re[i].hypot(im[i])).collect::<Vec<_>>();
+        let peak = (0..magnitudes.len())
+            .max_by(|&a, &b| magnitudes[a].total_cmp(&magnitudes[b]))
+            .unwrap();
+        assert_eq!(peak, k);
+        // a unit sine wave's amplitude is `n / 2` without windowing
+        assert!((magnitudes[k] - (n / 2) as f64).abs() < 1e-6);
+        assert!(magnitudes
+            .iter()
+            .enumerate()
+            .all(|(i, m)| i == k || *m < 1e-6));
+    }
+}
@@ -0,0 +1,218 @@
+use super::{
+    config, ClientRequest, Command, ContextPageType, InputEffect, Key, KeyCode, KeySequence,
+    LineInput, PageState, Playback, PlayerRequest, PopupState, Result, SearchFocusState,
+    SearchPageUIState, SharedState, UIStateGuard,
+};
+use crate::{
+    ex_command::{self, ExCommand, OpenTarget},
+    state::{
+        load_data_from_file_cache, store_data_into_file_cache, CommandLinePopupState, FileCacheKey,
+    },
+};
+use anyhow::Context as _;
+
+/// the maximum number of commands kept in the command line's history
+const HISTORY_SIZE: usize = 100;
+
+/// Open the command line popup with its history loaded from the cache folder
+pub fn open_command_line(
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    // refresh the devices used to complete the `device` command
+    client_pub.send(ClientRequest::GetDevices)?;
+
+    let history = load_data_from_file_cache(
+        FileCacheKey::CommandLineHistory,
+        &config::get_config().cache_folder,
+    )
+    .unwrap_or_default();
+    ui.popup = Some(PopupState::CommandLine(CommandLinePopupState::new(history)));
+    Ok(())
+}
+
+pub fn handle_key_sequence_for_command_line_popup(
+    key_sequence: &KeySequence,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let Some(PopupState::CommandLine(popup)) = &mut ui.popup else {
+        return Ok(false);
+    };
+
+    if key_sequence.keys.len() == 1 {
+        match &key_sequence.keys[0] {
+            Key::None(KeyCode::Enter) => {
+                let input = popup.input.get_text();
+                match input.parse::<ExCommand>() {
+                    Ok(command) => {
+                        save_history(&mut popup.history, input.trim());
+                        ui.popup = None;
+                        handle_ex_command(command, client_pub, state, ui)?;
+                    }
+                    // keep the popup open to show the error
+                    Err(err) => popup.error = Some(format!("{err:#}")),
+                }
+                return Ok(true);
+            }
+            Key::None(KeyCode::Tab) => {
+                if popup.completions.is_empty() {
+                    popup.completions = ex_command::complete(&popup.input.get_text(), state);
+                    popup.completion_id = 0;
+                } else {
+                    popup.completion_id = (popup.completion_id + 1) % popup.completions.len();
+                }
+                if let Some(completion) = popup.completions.get(popup.completion_id) {
+                    popup.input.set_text(completion);
+                }
+                return Ok(true);
+            }
+            Key::None(KeyCode::Up) => {
+                let id = match popup.history_id {
+                    Some(id) => id.saturating_sub(1),
+                    None => popup.history.len().saturating_sub(1),
+                };
+                if let Some(command) = popup.history.get(id) {
+                    popup.input.set_text(command);
+                    popup.history_id = Some(id);
+                }
+                return Ok(true);
+            }
+            Key::None(KeyCode::Down) => {
+                if let Some(id) = popup.history_id {
+                    if let Some(command) = popup.history.get(id + 1) {
+                        popup.input.set_text(command);
+                        popup.history_id = Some(id + 1);
+                    } else {
+                        popup.input = LineInput::default();
+                        popup.history_id = None;
+                    }
+                }
+                return Ok(true);
+            }
+            Key::None(KeyCode::Backspace) if popup.input.is_empty() => {
+                // close the command line when pressing backspace on an empty input
+                ui.popup = None;
+                return Ok(true);
+            }
+            k => {
+                if let Some(effect) = popup.input.input(k) {
+                    if let InputEffect::TextChanged = effect {
+                        popup.completions.clear();
+                        popup.history_id = None;
+                        popup.error = None;
+                    }
+                    return Ok(true);
+                }
+            }
+        }
+    }
+
+    match config::get_config()
+        .keymap_config
+        .find_command_from_key_sequence(key_sequence)
+    {
+        Some(Command::ClosePopup) => {
+            ui.popup = None;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Add an entered command to the command line's history and persist the history
+fn save_history(history: &mut Vec<String>, command: &str) {
+    history.retain(|c| c != command);
+    history.push(command.to_string());
+    if history.len() > HISTORY_SIZE {
+        history.drain(..history.len() - HISTORY_SIZE);
+    }
+
+    if let Err(err) = store_data_into_file_cache(
+        FileCacheKey::CommandLineHistory,
+        &config::get_config().cache_folder,
+        history,
+    ) {
+        tracing::warn!("Failed to store the command line's history: {err:#}");
+    }
+}
+
+/// Handle a command entered in the command line
+pub fn handle_ex_command(
+    command: ExCommand,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    match command {
+        ExCommand::Volume { percent, is_offset } => {
+            let percent = if is_offset {
+                let volume = state
+                    .player
+                    .read()
+                    .buffered_playback
+                    .as_ref()
+                    .and_then(|p| p.volume)
+                    .context("playback has no volume")?;
+                (volume as i32 + i32::from(percent)).clamp(0, 100)
+            } else {
+                i32::from(percent)
+            };
+            client_pub.send(ClientRequest::Player(PlayerRequest::Volume(percent as u8)))?;
+        }
+        ExCommand::Seek {
+            position_ms,
+            is_offset,
+        } => {
+            let position = chrono::Duration::try_milliseconds(position_ms).unwrap_or_default();
+            let position = if is_offset {
+                let progress = state
+                    .player
+                    .read()
+                    .playback_progress()
+                    .context("no active playback found")?;
+                progress + position
+            } else {
+                position
+            };
+            client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
+                std::cmp::max(chrono::Duration::zero(), position),
+            )))?;
+        }
+        ExCommand::Search(query) => {
+            let mut search_state = SearchPageUIState::new();
+            search_state.focus = SearchFocusState::Tracks;
+            let mut line_input = LineInput::default();
+            line_input.set_text(&query);
+            ui.new_page(PageState::Search {
+                line_input,
+                current_query: query.clone(),
+                state: search_state,
+            });
+            client_pub.send(ClientRequest::Search(query))?;
+        }
+        ExCommand::Open(link) => match OpenTarget::from_link(&link)? {
+            OpenTarget::Track(id) => {
+                client_pub.send(ClientRequest::Player(PlayerRequest::StartPlayback(
+                    Playback::URIs(vec![id.into()], None),
+                    None,
+                )))?;
+            }
+            OpenTarget::Context(id) => {
+                ui.new_page(PageState::Context {
+                    id: None,
+                    context_page_type: ContextPageType::Browsing(id),
+                    state: None,
+                });
+            }
+        },
+        ExCommand::Theme(name) => {
+            ui.theme = ex_command::find_theme(&name)?;
+        }
+        ExCommand::Device(name) => {
+            client_pub.send(ClientRequest::ConnectDevice(name))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use super::{
+    handle_global_action, handle_global_command, page, popup, ActionTarget, ArtistFocusState,
+    ClientRequest, CommandOrAction, Context, ContextPageUIState, PageState, PlaylistFolderItem,
+    Result, SearchFocusState, SharedState, Track, UIStateGuard,
+};
+use crate::{
+    command::MacroStep,
+    state::{Id, PlayableId},
+};
+use anyhow::Context as _;
+
+/// Handle a macro by running its steps in order.
+///
+/// A macro stops at the first step that fails.
+pub fn handle_macro(
+    steps: &[MacroStep],
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    for step in steps {
+        tracing::debug!("Handling macro step: {step:?}");
+        let handled = match step {
+            MacroStep::Command(command) => {
+                handle_command_or_action(CommandOrAction::Command(*command), client_pub, state, ui)?
+            }
+            MacroStep::Action(action) => handle_command_or_action(
+                CommandOrAction::Action(*action, ActionTarget::SelectedItem),
+                client_pub,
+                state,
+                ui,
+            )?,
+            MacroStep::PlayingTrackAction(action) => handle_command_or_action(
+                CommandOrAction::Action(*action, ActionTarget::PlayingTrack),
+                client_pub,
+                state,
+                ui,
+            )?,
+            MacroStep::AddToPlaylist { playlist, target } => {
+                add_to_playlist(playlist, *target, client_pub, state, ui)?;
+                true
+            }
+            MacroStep::SwitchDevice { device } => {
+                client_pub.send(ClientRequest::ConnectDevice(device.clone()))?;
+                true
+            }
+        };
+        if !handled {
+            anyhow::bail!("macro step {step:?} can't be handled in the current page or popup");
+        }
+    }
+    Ok(())
+}
+
+/// Handle a command or an action the same way as when its key sequence is pressed:
+/// the current popup (or page) handles it first, then the global handler.
+fn handle_command_or_action(
+    command_or_action: CommandOrAction,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let handled = match (&ui.popup, command_or_action) {
+        (None, _) => {
+            page::handle_command_or_action_for_page(command_or_action, client_pub, state, ui)?
+        }
+        (Some(_), CommandOrAction::Command(command)) => {
+            popup::handle_command_for_popup(command, client_pub, state, ui)?
+        }
+        (Some(_), CommandOrAction::Action(..)) => false,
+    };
+    if handled {
+        return Ok(true);
+    }
+
+    match command_or_action {
+        CommandOrAction::Command(command) => handle_global_command(command, client_pub, state, ui),
+        CommandOrAction::Action(action, target) => {
+            handle_global_action(action, target, client_pub, state, ui)
+        }
+    }
+}
+
+/// Add the selected item (or the playing track) to a playlist specified by its ID or name
+fn add_to_playlist(
+    playlist: &str,
+    target: ActionTarget,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    let playlist_id = state
+        .data
+        .read()
+        .user_data
+        .modifiable_playlist_items(None)
+        .into_iter()
+        .find_map(|item| match item {
+            PlaylistFolderItem::Playlist(p)
+                if p.id.id() == playlist || p.name.eq_ignore_ascii_case(playlist) =>
+            {
+                Some(p.id.clone())
+            }
+            _ => None,
+        })
+        .with_context(|| format!("no modifiable playlist with id or name={playlist} found"))?;
+
+    let ids = target_playable_ids(target, state, ui);
+    if ids.is_empty() {
+        anyhow::bail!("no {target:?} item to add to a playlist");
+    }
+    client_pub.send(ClientRequest::AddPlayablesToPlaylist(playlist_id, ids))?;
+    // the marked tracks (if any) are added like any other bulk action on them
+    if target == ActionTarget::SelectedItem {
+        ui.track_marks.clear();
+    }
+    Ok(())
+}
+
+/// Get the IDs of the items targeted by an action: the marked tracks or the selected
+/// track (or episode) of the current page, or the playing track (or episode).
+///
+/// The items are found the same way as when handling the action.
+fn target_playable_ids(
+    target: ActionTarget,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Vec<PlayableId<'static>> {
+    if target == ActionTarget::PlayingTrack {
+        return match state.player.read().currently_playing() {
+            Some(rspotify::model::PlayableItem::Track(track)) => track
+                .id
+                .clone()
+                .map(|id| vec![id.into()])
+                .unwrap_or_default(),
+            Some(rspotify::model::PlayableItem::Episode(episode)) => {
+                vec![episode.id.clone().into()]
+            }
+            None => vec![],
+        };
+    }
+
+    let data = state.data.read();
+    let selected = ui.current_page_mut().selected().unwrap_or_default();
+    match ui.current_page() {
+        PageState::Context {
+            id: Some(id),
+            state: page_state,
+            ..
+        } => match data.caches.context.get(&id.uri()) {
+            Some(Context::Artist { top_tracks, .. }) => match page_state {
+                Some(ContextPageUIState::Artist {
+                    focus: ArtistFocusState::TopTracks,
+                    ..
+                }) => selected_track_ids(&ui.search_filtered_items(top_tracks), selected, ui),
+                _ => vec![],
+            },
+            Some(
+                Context::Album { tracks, .. }
+                | Context::Tracks { tracks, .. }
+                | Context::Playlist { tracks, .. },
+            ) => selected_track_ids(&ui.search_filtered_items(tracks), selected, ui),
+            Some(Context::Show { episodes, .. }) => ui
+                .search_filtered_items(episodes)
+                .get(selected)
+                .map(|e| vec![e.id.clone().into()])
+                .unwrap_or_default(),
+            None => vec![],
+        },
+        PageState::Search {
+            state: page_state,
+            current_query,
+            ..
+        } => {
+            let Some(results) = data.caches.search.get(current_query) else {
+                return vec![];
+            };
+            match page_state.focus {
+                SearchFocusState::Tracks => results
+                    .tracks
+                    .get(selected)
+                    .map(|t| vec![t.id.clone().into()])
+                    .unwrap_or_default(),
+                SearchFocusState::Episodes => results
+                    .episodes
+                    .get(selected)
+                    .map(|e| vec![e.id.clone().into()])
+                    .unwrap_or_default(),
+                _ => vec![],
+            }
+        }
+        _ => vec![],
+    }
+}
+
+/// Get the IDs of the marked tracks of a track table if any, otherwise of the selected track
+fn selected_track_ids(
+    tracks: &[&Track],
+    selected: usize,
+    ui: &UIStateGuard,
+) -> Vec<PlayableId<'static>> {
+    let marked_tracks = ui.track_marks.marked_tracks(tracks, selected);
+    let tracks = if marked_tracks.is_empty() {
+        tracks.get(selected).into_iter().copied().collect()
+    } else {
+        marked_tracks
+    };
+    tracks.into_iter().map(|t| t.id.clone().into()).collect()
+}
@@ -8,25 +8,34 @@ use crate::{
     key::{Key, KeySequence},
     state::{
         ActionListItem, Album, AlbumId, Artist, ArtistFocusState, ArtistId, ArtistPopupAction,
-        BrowsePageUIState, Context, ContextId, ContextPageType, ContextPageUIState, DataReadGuard,
-        Focusable, Id, Item, ItemId, LibraryFocusState, LibraryPageUIState, PageState, PageType,
-        PlayableId, Playback, PlaylistCreateCurrentField, PlaylistFolderItem, PlaylistId,
-        PlaylistPopupAction, PopupState, SearchFocusState, SearchPageUIState, SharedState, ShowId,
-        Track, TrackId, TrackOrder, UIStateGuard, USER_LIKED_TRACKS_ID,
-        USER_RECENTLY_PLAYED_TRACKS_ID, USER_TOP_TRACKS_ID,
+        BrowsePageUIState, CommandPaletteEntry, Context, ContextId, ContextPageType,
+        ContextPageUIState, DataReadGuard, Focusable, Id, Item, ItemId, LibraryFocusState,
+        LibraryPageUIState, PageState, PageType, PlayableId, Playback, PlaylistCreateCurrentField,
+        PlaylistFolderItem, PlaylistId, PlaylistPopupAction, PopupState, SearchFocusState,
+        SearchPageUIState, SharedState, ShowId, SleepTimer, SleepTimerMode, Track, TrackId,
+        TrackOrder, UIStateGuard, USER_LIKED_TRACKS_ID, USER_RECENTLY_PLAYED_TRACKS_ID,
+        USER_TOP_TRACKS_ID,
+    },
+    ui::{
+        single_line_input::{InputEffect, LineInput},
+        Orientation,
     },
-    ui::{single_line_input::LineInput, Orientation},
     utils::parse_uri,
 };
 
-use crate::utils::map_join;
 use anyhow::{Context as _, Result};
 use crossterm::event::KeyCode;
 
 use clipboard::{execute_copy_command, get_clipboard_content};
 use ratatui::widgets::ListState;
 
+#[cfg(feature = "streaming")]
+use crate::state::VisualizerMode;
+
 mod clipboard;
+mod command_line;
+mod macros;
+mod mouse;
 mod page;
 mod popup;
 mod window;
@@ -36,7 +45,9 @@ pub fn start_event_handler(state: &SharedState, client_pub: &flume::Sender<Clien
     while let Ok(event) = crossterm::event::read() {
         let _enter = tracing::info_span!("terminal_event", event = ?event).entered();
         if let Err(err) = match event {
-            crossterm::event::Event::Mouse(event) => handle_mouse_event(event, client_pub, state),
+            crossterm::event::Event::Mouse(event) => {
+                mouse::handle_mouse_event(event, client_pub, state)
+            }
             crossterm::event::Event::Resize(columns, rows) => {
                 state.ui.lock().orientation = Orientation::from_size(columns, rows);
                 Ok(())
@@ -59,38 +70,6 @@ pub fn start_event_handler(state: &SharedState, client_pub: &flume::Sender<Clien
     }
 }
 
-// Handle a terminal mouse event
-fn handle_mouse_event(
-    event: crossterm::event::MouseEvent,
-    client_pub: &flume::Sender<ClientRequest>,
-    state: &SharedState,
-) -> Result<()> {
-    // a left click event
-    if let crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left) = event.kind
-    {
-        tracing::debug!("Handling mouse event: {event:?}");
-        let rect = state.ui.lock().playback_progress_bar_rect;
-        if event.row == rect.y {
-            // calculate the seek position (in ms) based on the mouse click position,
-            // the progress bar's width and the track's duration (in ms)
-            let player = state.player.read();
-            let duration = match player.currently_playing() {
-                Some(rspotify::model::PlayableItem::Track(track)) => Some(track.duration),
-                Some(rspotify::model::PlayableItem::Episode(episode)) => Some(episode.duration),
-                None => None,
-            };
-            if let Some(duration) = duration {
-                let position_ms =
-                    (duration.num_milliseconds()) * i64::from(event.column) / i64::from(rect.width);
-                client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
-                    chrono::Duration::try_milliseconds(position_ms).unwrap(),
-                )))?;
-            }
-        }
-    }
-    Ok(())
-}
-
 // Handle a terminal key pressed event
 fn handle_key_event(
     event: crossterm::event::KeyEvent,
@@ -133,7 +112,13 @@ fn handle_key_event(
             Some(CommandOrAction::Command(command)) => {
                 handle_global_command(command, client_pub, state, &mut ui)?
             }
-            None => false,
+            None => match keymap_config.find_macro_from_key_sequence(&key_sequence) {
+                Some(steps) => {
+                    macros::handle_macro(steps, client_pub, state, &mut ui)?;
+                    true
+                }
+                None => false,
+            },
         }
     };
 
@@ -209,9 +194,9 @@ pub fn handle_action_in_context(
             Action::AddToPlaylist => {
                 client_pub.send(ClientRequest::GetUserPlaylists)?;
                 ui.popup = Some(PopupState::UserPlaylistList(
-                    PlaylistPopupAction::AddTrack {
+                    PlaylistPopupAction::AddTracks {
                         folder_id: 0,
-                        track_id: track.id,
+                        track_ids: vec![track.id],
                     },
                     ListState::default(),
                 ));
@@ -270,9 +255,9 @@ pub fn handle_action_in_context(
                     ..
                 } = ui.current_page()
                 {
-                    client_pub.send(ClientRequest::DeleteTrackFromPlaylist(
+                    client_pub.send(ClientRequest::DeleteTracksFromPlaylist(
                         playlist_id.clone_static(),
-                        track.id,
+                        vec![track.id],
                     ))?;
                 }
                 ui.popup = None;
@@ -280,6 +265,15 @@ pub fn handle_action_in_context(
             }
             _ => Ok(false),
         },
+        ActionContext::Tracks(tracks) => {
+            let handled = handle_action_on_tracks(action, tracks, client_pub, data, ui)?;
+            // the tracks added to a playlist are only unmarked once a playlist is chosen,
+            // so that cancelling the playlist popup keeps the marks
+            if handled && action != Action::AddToPlaylist {
+                ui.track_marks.clear();
+            }
+            Ok(handled)
+        }
         ActionContext::Album(album) => match action {
             Action::GoToArtist => {
                 handle_go_to_artist(album.artists, ui);
@@ -320,6 +314,18 @@ pub fn handle_action_in_context(
                 ui.popup = None;
                 Ok(true)
             }
+            #[cfg(feature = "streaming")]
+            Action::PinForOffline => {
+                client_pub.send(ClientRequest::PinContext(ContextId::Album(album.id)))?;
+                ui.popup = None;
+                Ok(true)
+            }
+            #[cfg(feature = "streaming")]
+            Action::UnpinFromOffline => {
+                client_pub.send(ClientRequest::UnpinContext(album.id.uri()))?;
+                ui.popup = None;
+                Ok(true)
+            }
             _ => Ok(false),
         },
         ActionContext::Artist(artist) => match action {
@@ -381,6 +387,18 @@ pub fn handle_action_in_context(
                 ui.popup = None;
                 Ok(true)
             }
+            #[cfg(feature = "streaming")]
+            Action::PinForOffline => {
+                client_pub.send(ClientRequest::PinContext(ContextId::Playlist(playlist.id)))?;
+                ui.popup = None;
+                Ok(true)
+            }
+            #[cfg(feature = "streaming")]
+            Action::UnpinFromOffline => {
+                client_pub.send(ClientRequest::UnpinContext(playlist.id.uri()))?;
+                ui.popup = None;
+                Ok(true)
+            }
             _ => Ok(false),
         },
         ActionContext::Show(show) => match action {
@@ -460,6 +478,69 @@ pub fn handle_action_in_context(
     }
 }
 
+/// Handle an action on multiple (marked) tracks
+fn handle_action_on_tracks(
+    action: Action,
+    tracks: Vec<Track>,
+    client_pub: &flume::Sender<ClientRequest>,
+    data: &DataReadGuard,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    match action {
+        Action::AddToQueue => {
+            client_pub.send(ClientRequest::AddPlayablesToQueue(
+                tracks.into_iter().map(|t| t.id.into()).collect(),
+            ))?;
+            ui.popup = None;
+        }
+        Action::AddToPlaylist => {
+            client_pub.send(ClientRequest::GetUserPlaylists)?;
+            ui.popup = Some(PopupState::UserPlaylistList(
+                PlaylistPopupAction::AddTracks {
+                    folder_id: 0,
+                    track_ids: tracks.into_iter().map(|t| t.id).collect(),
+                },
+                ListState::default(),
+            ));
+        }
+        Action::ToggleLiked => {
+            if tracks.iter().all(|t| data.user_data.is_liked_track(t)) {
+                client_pub.send(ClientRequest::DeleteTracksFromLiked(
+                    tracks.into_iter().map(|t| t.id).collect(),
+                ))?;
+            } else {
+                client_pub.send(ClientRequest::AddTracksToLiked(tracks))?;
+            }
+            ui.popup = None;
+        }
+        Action::AddToLiked => {
+            client_pub.send(ClientRequest::AddTracksToLiked(tracks))?;
+            ui.popup = None;
+        }
+        Action::DeleteFromLiked => {
+            client_pub.send(ClientRequest::DeleteTracksFromLiked(
+                tracks.into_iter().map(|t| t.id).collect(),
+            ))?;
+            ui.popup = None;
+        }
+        Action::DeleteFromPlaylist => {
+            if let PageState::Context {
+                id: Some(ContextId::Playlist(playlist_id)),
+                ..
+            } = ui.current_page()
+            {
+                client_pub.send(ClientRequest::DeleteTracksFromPlaylist(
+                    playlist_id.clone_static(),
+                    tracks.into_iter().map(|t| t.id).collect(),
+                ))?;
+            }
+            ui.popup = None;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
 fn handle_go_to_artist(artists: Vec<Artist>, ui: &mut UIStateGuard) {
     if artists.len() == 1 {
         let context_id = ContextId::Artist(artists[0].id.clone());
@@ -572,11 +653,18 @@ fn handle_global_command(
             client_pub.send(ClientRequest::Player(PlayerRequest::Shuffle))?;
         }
         Command::VolumeChange { offset } => {
-            if let Some(ref playback) = state.player.read().buffered_playback {
-                if let Some(volume) = playback.volume {
-                    let volume = std::cmp::min(volume as i32 + offset, 100_i32);
-                    client_pub.send(ClientRequest::Player(PlayerRequest::Volume(volume as u8)))?;
-                }
+            let volume = state
+                .player
+                .read()
+                .buffered_playback
+                .as_ref()
+                .and_then(|p| p.volume);
+            // without a playback, the volume of the offline player (if any) is changed
+            #[cfg(feature = "streaming")]
+            let volume = volume.or_else(|| state.offline_player.running_volume().map(u32::from));
+            if let Some(volume) = volume {
+                let volume = std::cmp::min(volume as i32 + offset, 100_i32);
+                client_pub.send(ClientRequest::Player(PlayerRequest::Volume(volume as u8)))?;
             }
         }
         Command::Mute => {
@@ -708,6 +796,7 @@ fn handle_global_command(
             if ui.history.len() > 1 {
                 ui.history.pop();
                 ui.popup = None;
+                ui.track_marks.clear();
             }
         }
         Command::OpenSpotifyLinkFromClipboard => {
@@ -762,20 +851,25 @@ fn handle_global_command(
             if let Some(rspotify::model::PlayableItem::Track(track)) =
                 state.player.read().currently_playing()
             {
-                if let Some(id) = &track.id {
-                    let artists = map_join(&track.artists, |a| &a.name, ", ");
+                if let Some(track) = Track::try_from_full_track(track.clone()) {
                     ui.new_page(PageState::Lyrics {
-                        track_uri: id.uri(),
+                        track_uri: track.id.uri(),
                         track: track.name.clone(),
-                        artists,
+                        artists: track.artists_info(),
+                        cursor: ListState::default(),
+                        scroll_offset: 0,
                     });
 
-                    client_pub.send(ClientRequest::GetLyrics {
-                        track_id: id.clone_static(),
-                    })?;
+                    client_pub.send(ClientRequest::GetLyrics { track })?;
                 }
             }
         }
+        #[cfg(feature = "streaming")]
+        Command::VisualizerPage => {
+            ui.new_page(PageState::Visualizer {
+                mode: VisualizerMode::Spectrum,
+            });
+        }
         Command::SwitchDevice => {
             ui.popup = Some(PopupState::DeviceList(ListState::default()));
             client_pub.send(ClientRequest::GetDevices)?;
@@ -792,6 +886,35 @@ fn handle_global_command(
             ui.popup = Some(PopupState::ThemeList(themes, ListState::default()));
         }
         #[cfg(feature = "streaming")]
+        Command::SwitchDspPreset => {
+            // disabling the DSP stage is the first option
+            let presets = std::iter::once(None)
+                .chain(
+                    config::get_config()
+                        .app_config
+                        .dsp_presets
+                        .iter()
+                        .map(|p| Some(p.name.clone())),
+                )
+                .collect();
+            ui.popup = Some(PopupState::DspPresetList(presets, ListState::default()));
+        }
+        Command::OpenCommandPalette => {
+            popup::open_command_palette(client_pub, state, ui)?;
+        }
+        Command::OpenCommandLine => {
+            command_line::open_command_line(client_pub, ui)?;
+        }
+        Command::SleepTimer => {
+            // cancelling the sleep timer is the first option
+            let modes = [15, 30, 45, 60, 90]
+                .into_iter()
+                .map(|minutes| SleepTimerMode::After { minutes })
+                .chain([SleepTimerMode::EndOfTrack, SleepTimerMode::EndOfContext]);
+            let modes = std::iter::once(None).chain(modes.map(Some)).collect();
+            ui.popup = Some(PopupState::SleepTimerList(modes, ListState::default()));
+        }
+        #[cfg(feature = "streaming")]
         Command::RestartIntegratedClient => {
             client_pub.send(ClientRequest::RestartIntegratedClient)?;
         }
@@ -0,0 +1,224 @@
+use super::{
+    page, popup, ClientRequest, Command, CommandOrAction, ListState, PlayerRequest, PopupState,
+    Result, SharedState, UIStateGuard,
+};
+use crate::state::{PageWindow, WindowArea};
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Position;
+use std::time::{Duration, Instant};
+
+/// the maximum interval between the two clicks of a double click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A list/table window under the mouse cursor
+#[derive(Clone, Copy)]
+enum MouseTarget {
+    Popup(WindowArea),
+    Page(PageWindow, WindowArea),
+}
+
+/// Handle a terminal mouse event
+pub fn handle_mouse_event(
+    event: MouseEvent,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+) -> Result<()> {
+    tracing::debug!("Handling mouse event: {event:?}");
+    let position = Position::new(event.column, event.row);
+    let mut ui = state.ui.lock();
+
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let rect = ui.playback_progress_bar_rect;
+            if event.row == rect.y {
+                // calculate the seek position (in ms) based on the mouse click position,
+                // the progress bar's width and the track's duration (in ms)
+                let player = state.player.read();
+                let duration = match player.currently_playing() {
+                    Some(rspotify::model::PlayableItem::Track(track)) => Some(track.duration),
+                    Some(rspotify::model::PlayableItem::Episode(episode)) => Some(episode.duration),
+                    None => None,
+                };
+                if let Some(duration) = duration {
+                    let position_ms = (duration.num_milliseconds()) * i64::from(event.column)
+                        / i64::from(rect.width);
+                    client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
+                        chrono::Duration::try_milliseconds(position_ms).unwrap(),
+                    )))?;
+                }
+                return Ok(());
+            }
+
+            let is_double_click = ui.last_mouse_click.is_some_and(|(time, last_position)| {
+                last_position == position && time.elapsed() <= DOUBLE_CLICK_INTERVAL
+            });
+            ui.last_mouse_click = if is_double_click {
+                None
+            } else {
+                Some((Instant::now(), position))
+            };
+
+            match find_mouse_target(&ui, position) {
+                Some(target) => {
+                    if select_item_at(target, position, client_pub, state, &mut ui)?
+                        && is_double_click
+                    {
+                        handle_command_for_target(
+                            target,
+                            Command::ChooseSelected,
+                            client_pub,
+                            state,
+                            &mut ui,
+                        )?;
+                    }
+                }
+                None => {
+                    // clicking outside of a list popup closes the popup
+                    if ui.has_focused_popup() && ui.popup_window.is_some() {
+                        popup::handle_command_for_popup(
+                            Command::ClosePopup,
+                            client_pub,
+                            state,
+                            &mut ui,
+                        )?;
+                    }
+                }
+            }
+        }
+        MouseEventKind::Down(MouseButton::Right) => {
+            if let Some(target @ MouseTarget::Page(..)) = find_mouse_target(&ui, position) {
+                if select_item_at(target, position, client_pub, state, &mut ui)? {
+                    handle_command_for_target(
+                        target,
+                        Command::ShowActionsOnSelectedItem,
+                        client_pub,
+                        state,
+                        &mut ui,
+                    )?;
+                }
+            }
+        }
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+            let command = if event.kind == MouseEventKind::ScrollUp {
+                Command::SelectPreviousOrScrollUp
+            } else {
+                Command::SelectNextOrScrollDown
+            };
+
+            if ui.has_focused_popup() {
+                popup::handle_command_for_popup(command, client_pub, state, &mut ui)?;
+            } else {
+                // scroll the window under the mouse cursor, or the focused window otherwise
+                if let Some(MouseTarget::Page(window, _)) = find_mouse_target(&ui, position) {
+                    ui.current_page_mut().focus_window(window);
+                }
+                page::handle_command_or_action_for_page(
+                    CommandOrAction::Command(command),
+                    client_pub,
+                    state,
+                    &mut ui,
+                )?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Find the list/table window under the mouse cursor.
+///
+/// If there is a focused popup, only the popup's window is considered.
+fn find_mouse_target(ui: &UIStateGuard, position: Position) -> Option<MouseTarget> {
+    if ui.has_focused_popup() {
+        return ui
+            .popup_window
+            .filter(|area| area.rect.contains(position))
+            .map(MouseTarget::Popup);
+    }
+    ui.page_windows
+        .iter()
+        .find(|(_, area)| area.rect.contains(position))
+        .map(|&(window, area)| MouseTarget::Page(window, area))
+}
+
+/// Select the item at the mouse cursor's position in a window, focusing the window if needed.
+///
+/// Return `false` if there is no item at the position.
+fn select_item_at(
+    target: MouseTarget,
+    position: Position,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    match target {
+        MouseTarget::Popup(area) => {
+            let Some(offset) = ui
+                .popup
+                .as_ref()
+                .and_then(PopupState::list_state)
+                .map(ListState::offset)
+            else {
+                return Ok(false);
+            };
+            let Some(id) = area.item_at(position, offset) else {
+                return Ok(false);
+            };
+
+            // move the popup's selection step by step, so that the popup's
+            // callbacks on selecting an item (e.g. previewing a theme) are run
+            loop {
+                let current = popup_selected(ui);
+                let command = match current.cmp(&id) {
+                    std::cmp::Ordering::Less => Command::SelectNextOrScrollDown,
+                    std::cmp::Ordering::Greater => Command::SelectPreviousOrScrollUp,
+                    std::cmp::Ordering::Equal => return Ok(true),
+                };
+                popup::handle_command_for_popup(command, client_pub, state, ui)?;
+                if popup_selected(ui) == current {
+                    return Ok(false);
+                }
+            }
+        }
+        MouseTarget::Page(window, area) => {
+            let page = ui.current_page_mut();
+            if !page.focus_window(window) {
+                return Ok(false);
+            }
+            let Some(mut window_state) = page.focus_window_state_mut() else {
+                return Ok(false);
+            };
+            let Some(id) = area.item_at(position, window_state.offset()) else {
+                return Ok(false);
+            };
+            window_state.select(id);
+            Ok(true)
+        }
+    }
+}
+
+fn popup_selected(ui: &UIStateGuard) -> usize {
+    ui.popup
+        .as_ref()
+        .and_then(PopupState::list_selected)
+        .unwrap_or_default()
+}
+
+/// Handle a command for the window under the mouse cursor
+fn handle_command_for_target(
+    target: MouseTarget,
+    command: Command,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    match target {
+        MouseTarget::Popup(_) => popup::handle_command_for_popup(command, client_pub, state, ui),
+        MouseTarget::Page(..) => page::handle_command_or_action_for_page(
+            CommandOrAction::Command(command),
+            client_pub,
+            state,
+            ui,
+        ),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::state::{store_data_into_file_cache, FileCacheKey};
 use anyhow::Context as _;
 use command::CommandOrAction;
 
@@ -20,17 +21,38 @@ pub fn handle_key_sequence_for_page(
         .keymap_config
         .find_command_or_action_from_key_sequence(key_sequence)
     {
-        Some(CommandOrAction::Command(command)) => match page_type {
+        Some(found_keymap) => {
+            handle_command_or_action_for_page(found_keymap, client_pub, state, ui)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Handle a command or an action for the current page
+pub fn handle_command_or_action_for_page(
+    found_keymap: CommandOrAction,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let page_type = ui.current_page().page_type();
+    if page_type == PageType::Search {
+        return handle_command_or_action_for_search_page(found_keymap, client_pub, state, ui);
+    }
+
+    match found_keymap {
+        CommandOrAction::Command(command) => match page_type {
             PageType::Search => anyhow::bail!("page search type should already be handled!"),
             PageType::Library => handle_command_for_library_page(command, client_pub, ui, state),
             PageType::Context => handle_command_for_context_page(command, client_pub, ui, state),
             PageType::Browse => handle_command_for_browse_page(command, client_pub, ui, state),
-            // lyrics page doesn't support any commands
-            PageType::Lyrics => Ok(false),
+            PageType::Lyrics => handle_command_for_lyrics_page(command, client_pub, ui, state),
             PageType::Queue => Ok(handle_command_for_queue_page(command, ui)),
             PageType::CommandHelp => Ok(handle_command_for_command_help_page(command, ui)),
+            #[cfg(feature = "streaming")]
+            PageType::Visualizer => Ok(handle_command_for_visualizer_page(command, ui)),
         },
-        Some(CommandOrAction::Action(action, ActionTarget::SelectedItem)) => match page_type {
+        CommandOrAction::Action(action, ActionTarget::SelectedItem) => match page_type {
             PageType::Search => anyhow::bail!("page search type should already be handled!"),
             PageType::Library => handle_action_for_library_page(action, client_pub, ui, state),
             PageType::Context => {
@@ -39,7 +61,7 @@ pub fn handle_key_sequence_for_page(
             PageType::Browse => handle_action_for_browse_page(action, client_pub, ui, state),
             _ => Ok(false),
         },
-        _ => Ok(false),
+        CommandOrAction::Action(..) => Ok(false),
     }
 }
 
@@ -226,18 +248,38 @@ fn handle_key_sequence_for_search_page(
         }
     }
 
-    let Some(found_keymap) = config::get_config()
+    match config::get_config()
         .keymap_config
         .find_command_or_action_from_key_sequence(key_sequence)
-    else {
-        return Ok(false);
+    {
+        Some(found_keymap) => {
+            handle_command_or_action_for_search_page(found_keymap, client_pub, state, ui)
+        }
+        None => Ok(false),
+    }
+}
+
+fn handle_command_or_action_for_search_page(
+    found_keymap: CommandOrAction,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let (focus_state, current_query) = match ui.current_page() {
+        PageState::Search {
+            state,
+            current_query,
+            ..
+        } => (state.focus, current_query.clone()),
+        _ => anyhow::bail!("expect a search page"),
     };
 
     let data = state.data.read();
-    let search_results = data.caches.search.get(current_query);
+    let search_results = data.caches.search.get(&current_query);
 
     match focus_state {
-        SearchFocusState::Input => anyhow::bail!("user's search input should be handled before"),
+        // user's search input is handled before matching the key sequence
+        SearchFocusState::Input => Ok(false),
         SearchFocusState::Tracks => {
             let tracks = search_results
                 .map(|s| s.tracks.iter().collect::<Vec<_>>())
@@ -481,6 +523,119 @@ fn handle_command_for_browse_page(
     Ok(true)
 }
 
+fn handle_command_for_lyrics_page(
+    command: Command,
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
+    state: &SharedState,
+) -> Result<bool> {
+    let PageState::Lyrics {
+        track_uri,
+        track,
+        artists,
+        cursor,
+        ..
+    } = ui.current_page()
+    else {
+        anyhow::bail!("expect a lyrics page state");
+    };
+
+    match command {
+        Command::IncreaseLyricsOffset | Command::DecreaseLyricsOffset => {
+            let configs = config::get_config();
+            let step = i64::from(configs.app_config.lyrics_offset_step_ms);
+            let step = if command == Command::IncreaseLyricsOffset {
+                step
+            } else {
+                -step
+            };
+
+            let mut data = state.data.write();
+            let offsets = &mut data.user_data.lyrics_offsets;
+            let offset = offsets.get(track_uri).copied().unwrap_or_default() + step;
+            if offset == 0 {
+                offsets.remove(track_uri);
+            } else {
+                offsets.insert(track_uri.clone(), offset);
+            }
+            store_data_into_file_cache(FileCacheKey::LyricsOffsets, &configs.cache_folder, offsets)
+                .context("store lyrics offsets into the cache folder")?;
+            Ok(true)
+        }
+        Command::ChooseSelected => {
+            let Some(id) = cursor.selected() else {
+                return Ok(false);
+            };
+            let data = state.data.read();
+            let Some(Some(lyrics)) = data.caches.lyrics.get(track_uri) else {
+                return Ok(true);
+            };
+            if !lyrics.synced {
+                tracing::warn!("Cannot seek to an unsynced lyrics line of track {track}");
+                return Ok(true);
+            }
+            if let Some((t, _)) = lyrics.lines.get(id) {
+                // a line is shown once the playback's progress plus the lyrics offset reaches its timestamp
+                let position = *t - data.user_data.lyrics_offset(track_uri);
+                client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
+                    std::cmp::max(chrono::Duration::zero(), position),
+                )))?;
+            }
+            drop(data);
+            ui.current_page_mut().select_none();
+            Ok(true)
+        }
+        Command::FollowPlayingLyricsLine => {
+            ui.current_page_mut().select_none();
+            Ok(true)
+        }
+        Command::ExportLyrics => {
+            let Some(folder) = &config::get_config().app_config.lyrics_folder else {
+                tracing::warn!("Cannot export lyrics: `lyrics_folder` config option is not set");
+                return Ok(true);
+            };
+            let data = state.data.read();
+            let Some(Some(lyrics)) = data.caches.lyrics.get(track_uri) else {
+                tracing::warn!("Cannot export lyrics: no lyrics found for track {track}");
+                return Ok(true);
+            };
+
+            std::fs::create_dir_all(folder)?;
+            let path = folder.join(crate::utils::lrc_file_name(track, artists));
+            std::fs::write(&path, lyrics.to_lrc(track, artists))
+                .with_context(|| format!("write lyrics to {}", path.display()))?;
+            tracing::info!("Exported lyrics of track {track} to {}", path.display());
+            Ok(true)
+        }
+        _ => {
+            let data = state.data.read();
+            let Some(Some(lyrics)) = data.caches.lyrics.get(track_uri) else {
+                return Ok(false);
+            };
+            let len = lyrics.lines.len();
+            // start navigating from the currently playing line if no line is selected
+            let id = cursor.selected().unwrap_or_else(|| {
+                let progress = state
+                    .player
+                    .read()
+                    .playback_progress()
+                    .unwrap_or_else(chrono::Duration::zero)
+                    + data.user_data.lyrics_offset(track_uri);
+                lyrics.current_line_id(progress).unwrap_or_default()
+            });
+            drop(data);
+            let count = ui.count_prefix;
+            Ok(handle_navigation_command(
+                command,
+                ui.current_page_mut(),
+                id,
+                len,
+                count,
+            ))
+        }
+    }
+}
+
 fn handle_command_for_queue_page(command: Command, ui: &mut UIStateGuard) -> bool {
     let scroll_offset = match ui.current_page() {
         PageState::Queue { scroll_offset } => *scroll_offset,
@@ -503,6 +658,21 @@ fn handle_command_for_command_help_page(command: Command, ui: &mut UIStateGuard)
     handle_navigation_command(command, ui.current_page_mut(), scroll_offset, 10000, count)
 }
 
+#[cfg(feature = "streaming")]
+fn handle_command_for_visualizer_page(command: Command, ui: &mut UIStateGuard) -> bool {
+    let PageState::Visualizer { mode } = ui.current_page_mut() else {
+        return false;
+    };
+    if command != Command::ChooseSelected {
+        return false;
+    }
+    *mode = match mode {
+        VisualizerMode::Spectrum => VisualizerMode::Waveform,
+        VisualizerMode::Waveform => VisualizerMode::Spectrum,
+    };
+    true
+}
+
 pub fn handle_navigation_command(
     command: Command,
     page: &mut PageState,
@@ -1,6 +1,7 @@
 use super::*;
 use crate::command::construct_artist_actions;
 use anyhow::Context;
+use std::time::Duration;
 
 pub fn handle_key_sequence_for_popup(
     key_sequence: &KeySequence,
@@ -18,6 +19,22 @@ pub fn handle_key_sequence_for_popup(
         PopupState::PlaylistCreate { .. } => {
             return handle_key_sequence_for_create_playlist_popup(key_sequence, client_pub, ui);
         }
+        PopupState::CommandPalette { .. } => {
+            return handle_key_sequence_for_command_palette_popup(
+                key_sequence,
+                client_pub,
+                state,
+                ui,
+            );
+        }
+        PopupState::CommandLine(_) => {
+            return super::command_line::handle_key_sequence_for_command_line_popup(
+                key_sequence,
+                client_pub,
+                state,
+                ui,
+            );
+        }
         PopupState::ActionList(item, ..) => {
             return handle_key_sequence_for_action_list_popup(
                 item.n_actions(),
@@ -30,20 +47,67 @@ pub fn handle_key_sequence_for_popup(
         _ => {}
     }
 
-    let Some(command) = config::get_config()
+    match config::get_config()
         .keymap_config
         .find_command_from_key_sequence(key_sequence)
-    else {
-        return Ok(false);
-    };
+    {
+        Some(command) => handle_command_for_popup(command, client_pub, state, ui),
+        None => Ok(false),
+    }
+}
+
+/// Handle a command for the current popup
+pub fn handle_command_for_popup(
+    command: Command,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let popup = ui.popup.as_ref().context("empty popup")?;
 
     match popup {
-        PopupState::Search { .. } => anyhow::bail!("search popup should be handled before"),
-        PopupState::PlaylistCreate { .. } => {
-            anyhow::bail!("create playlist popup should be handled before")
-        }
-        PopupState::ActionList(..) => {
-            anyhow::bail!("action list popup should be handled before")
+        // search, create playlist and command line popups are handled via the raw key sequence
+        PopupState::Search { .. }
+        | PopupState::PlaylistCreate { .. }
+        | PopupState::CommandLine(_) => Ok(false),
+        PopupState::ActionList(item, ..) => handle_command_for_list_popup(
+            command,
+            ui,
+            item.n_actions(),
+            |_, _| {},
+            |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                handle_item_action(id, client_pub, state, ui)?;
+                Ok(())
+            },
+            |ui: &mut UIStateGuard| {
+                ui.popup = None;
+            },
+        ),
+        PopupState::CommandPalette {
+            query,
+            entries,
+            item,
+            ..
+        } => {
+            let entries = crate::state::filter_items(entries, &query.get_text())
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            let item = item.clone();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                entries.len(),
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    ui.popup = None;
+                    handle_command_palette_entry(&entries[id], item, client_pub, state, ui)
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
         }
         PopupState::ArtistList(_, artists, _) => {
             let n_items = artists.len();
@@ -125,11 +189,11 @@ pub fn handle_key_sequence_for_popup(
                     },
                 )
             }
-            PlaylistPopupAction::AddTrack {
+            PlaylistPopupAction::AddTracks {
                 folder_id,
-                track_id,
+                track_ids,
             } => {
-                let track_id = track_id.clone();
+                let track_ids = track_ids.clone();
                 let data = state.data.read();
                 let items = data.user_data.modifiable_playlist_items(Some(*folder_id));
 
@@ -141,17 +205,18 @@ pub fn handle_key_sequence_for_popup(
                     |ui: &mut UIStateGuard, id: usize| -> Result<()> {
                         ui.popup = match items.get(id).expect("invalid index") {
                             PlaylistFolderItem::Folder(f) => Some(PopupState::UserPlaylistList(
-                                PlaylistPopupAction::AddTrack {
+                                PlaylistPopupAction::AddTracks {
                                     folder_id: f.target_id,
-                                    track_id,
+                                    track_ids,
                                 },
                                 ListState::default(),
                             )),
                             PlaylistFolderItem::Playlist(p) => {
-                                client_pub.send(ClientRequest::AddPlayableToPlaylist(
+                                client_pub.send(ClientRequest::AddPlayablesToPlaylist(
                                     p.id.clone(),
-                                    track_id.into(),
+                                    track_ids.into_iter().map(Into::into).collect(),
                                 ))?;
+                                ui.track_marks.clear();
                                 None
                             }
                         };
@@ -185,9 +250,9 @@ pub fn handle_key_sequence_for_popup(
                                 ListState::default(),
                             )),
                             PlaylistFolderItem::Playlist(p) => {
-                                client_pub.send(ClientRequest::AddPlayableToPlaylist(
+                                client_pub.send(ClientRequest::AddPlayablesToPlaylist(
                                     p.id.clone(),
-                                    episode_id.into(),
+                                    vec![episode_id.into()],
                                 ))?;
                                 None
                             }
@@ -260,6 +325,61 @@ pub fn handle_key_sequence_for_popup(
                 },
             )
         }
+        #[cfg(feature = "streaming")]
+        PopupState::DspPresetList(presets, _) => {
+            let n_items = presets.len();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                n_items,
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    if let Some(PopupState::DspPresetList(ref presets, _)) = ui.popup {
+                        // the integrated player's sink applies the new preset to the next audio samples
+                        state.dsp_preset.write().clone_from(&presets[id]);
+                    }
+                    ui.popup = None;
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
+        PopupState::SleepTimerList(modes, _) => {
+            let n_items = modes.len();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                n_items,
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    if let Some(PopupState::SleepTimerList(ref modes, _)) = ui.popup {
+                        let mut player = state.player.write();
+                        let timer = modes[id].map(|mode| {
+                            let fade_out = Duration::from_secs(
+                                config::get_config().app_config.sleep_timer_fade_out_secs,
+                            );
+                            SleepTimer::new(mode, fade_out, &player)
+                        });
+                        let old_timer = std::mem::replace(&mut player.sleep_timer, timer);
+
+                        // restore the volume faded out by the replaced sleep timer
+                        if let Some(volume) = old_timer.and_then(|t| t.volume_before_fade_out) {
+                            client_pub
+                                .send(ClientRequest::Player(PlayerRequest::Volume(volume)))?;
+                        }
+                    }
+                    ui.popup = None;
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
         PopupState::DeviceList(_) => {
             let player = state.player.read();
 
@@ -331,6 +451,140 @@ fn handle_key_sequence_for_create_playlist_popup(
     Ok(false)
 }
 
+fn handle_key_sequence_for_command_palette_popup(
+    key_sequence: &KeySequence,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    // handle user's input that updates the palette's query
+    let Some(PopupState::CommandPalette {
+        query, list_state, ..
+    }) = &mut ui.popup
+    else {
+        return Ok(false);
+    };
+    if key_sequence.keys.len() == 1 {
+        if let Some(effect) = query.input(&key_sequence.keys[0]) {
+            if let InputEffect::TextChanged = effect {
+                list_state.select(Some(0));
+            }
+            return Ok(true);
+        }
+    }
+
+    match config::get_config()
+        .keymap_config
+        .find_command_from_key_sequence(key_sequence)
+    {
+        Some(command) => handle_command_for_popup(command, client_pub, state, ui),
+        None => Ok(false),
+    }
+}
+
+/// Open the command palette with all commands and the actions on the current page's selected item
+pub fn open_command_palette(
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    // the selected item's actions are constructed the same way as when opening its action list popup
+    let item = if !ui.has_focused_popup()
+        && page::handle_command_or_action_for_page(
+            CommandOrAction::Command(Command::ShowActionsOnSelectedItem),
+            client_pub,
+            state,
+            ui,
+        )? {
+        match ui.popup.take() {
+            Some(PopupState::ActionList(item, _)) => Some(item),
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    let keymap_config = &config::get_config().keymap_config;
+    let key_sequences = |keys: Vec<&KeySequence>| {
+        if keys.is_empty() {
+            String::new()
+        } else {
+            let keys = keys
+                .into_iter()
+                .map(|k| format!("\"{k}\""))
+                .collect::<Vec<_>>();
+            format!(" [{}]", keys.join(", "))
+        }
+    };
+
+    // commands with parameters (e.g. `VolumeChange`) are included via their keymaps
+    let mut commands = Command::all();
+    for keymap in &keymap_config.keymaps {
+        if keymap.command != Command::None && !commands.contains(&keymap.command) {
+            commands.push(keymap.command);
+        }
+    }
+    let mut entries = commands
+        .into_iter()
+        .map(|command| CommandPaletteEntry {
+            command: CommandOrAction::Command(command),
+            desc: format!(
+                "{command:?}: {}{}",
+                command.desc(),
+                key_sequences(keymap_config.find_key_sequences_from_command(command))
+            ),
+        })
+        .collect::<Vec<_>>();
+    if let Some(item) = &item {
+        entries.extend(item.actions().iter().map(|&action| CommandPaletteEntry {
+            command: CommandOrAction::Action(action, ActionTarget::SelectedItem),
+            desc: format!(
+                "{action:?}: act on \"{}\"{}",
+                item.name(),
+                key_sequences(keymap_config.find_key_sequences_from_action(action))
+            ),
+        }));
+    }
+
+    ui.popup = Some(PopupState::CommandPalette {
+        query: LineInput::default(),
+        entries,
+        item,
+        list_state: ListState::default(),
+    });
+    Ok(())
+}
+
+/// Handle an entry chosen in the command palette
+fn handle_command_palette_entry(
+    entry: &CommandPaletteEntry,
+    item: Option<Box<ActionListItem>>,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    match entry.command {
+        CommandOrAction::Command(command) => {
+            // similar to a key sequence, a command is handled by the current page first
+            if !page::handle_command_or_action_for_page(
+                CommandOrAction::Command(command),
+                client_pub,
+                state,
+                ui,
+            )? {
+                handle_global_command(command, client_pub, state, ui)?;
+            }
+        }
+        CommandOrAction::Action(action, _) => {
+            if let Some(item) = item {
+                let data = state.data.read();
+                handle_action_in_context(action, item.action_context(), client_pub, &data, ui)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 fn handle_key_sequence_for_search_popup(
     key_sequence: &KeySequence,
     client_pub: &flume::Sender<ClientRequest>,
@@ -485,19 +739,7 @@ fn handle_key_sequence_for_action_list_popup(
         return Ok(false);
     };
 
-    handle_command_for_list_popup(
-        command,
-        ui,
-        n_actions,
-        |_, _| {},
-        |ui: &mut UIStateGuard, id: usize| -> Result<()> {
-            handle_item_action(id, client_pub, state, ui)?;
-            Ok(())
-        },
-        |ui: &mut UIStateGuard| {
-            ui.popup = None;
-        },
-    )
+    handle_command_for_popup(command, client_pub, state, ui)
 }
 
 /// Handle the `n`-th action in an action list popup
@@ -513,25 +755,11 @@ pub fn handle_item_action(
     };
 
     let data = state.data.read();
-
-    match item {
-        ActionListItem::Track(track, actions) => {
-            handle_action_in_context(actions[n], track.into(), client_pub, &data, ui)
-        }
-        ActionListItem::Album(album, actions) => {
-            handle_action_in_context(actions[n], album.into(), client_pub, &data, ui)
-        }
-        ActionListItem::Artist(artist, actions) => {
-            handle_action_in_context(actions[n], artist.into(), client_pub, &data, ui)
-        }
-        ActionListItem::Playlist(playlist, actions) => {
-            handle_action_in_context(actions[n], playlist.into(), client_pub, &data, ui)
-        }
-        ActionListItem::Show(show, actions) => {
-            handle_action_in_context(actions[n], show.into(), client_pub, &data, ui)
-        }
-        ActionListItem::Episode(episode, actions) => {
-            handle_action_in_context(actions[n], episode.into(), client_pub, &data, ui)
-        }
-    }
+    handle_action_in_context(
+        item.actions()[n],
+        item.action_context(),
+        client_pub,
+        &data,
+        ui,
+    )
 }
@@ -9,6 +9,7 @@ use crate::{
 };
 use command::Action;
 use rand::Rng;
+use std::collections::HashSet;
 
 pub fn handle_action_for_focused_context_page(
     action: Action,
@@ -51,7 +52,7 @@ pub fn handle_action_for_focused_context_page(
                     ui,
                     client_pub,
                 ),
-                ArtistFocusState::TopTracks => handle_action_for_selected_item(
+                ArtistFocusState::TopTracks => handle_action_for_selected_tracks(
                     action,
                     &ui.search_filtered_items(top_tracks),
                     &data,
@@ -64,7 +65,7 @@ pub fn handle_action_for_focused_context_page(
             Context::Album { tracks, .. }
             | Context::Tracks { tracks, .. }
             | Context::Playlist { tracks, .. },
-        ) => handle_action_for_selected_item(
+        ) => handle_action_for_selected_tracks(
             action,
             &ui.search_filtered_items(tracks),
             &data,
@@ -97,6 +98,27 @@ pub fn handle_action_for_selected_item<T: Into<ActionContext> + Clone>(
     handle_action_in_context(action, items[id].clone().into(), client_pub, data, ui)
 }
 
+/// Handle an action for the marked tracks of a track table if any,
+/// otherwise for the selected track
+fn handle_action_for_selected_tracks(
+    action: Action,
+    tracks: &[&Track],
+    data: &DataReadGuard,
+    ui: &mut UIStateGuard,
+    client_pub: &flume::Sender<ClientRequest>,
+) -> Result<bool> {
+    if ui.track_marks.is_active() {
+        let id = ui.current_page_mut().selected().unwrap_or_default();
+        let marked_tracks = ui.track_marks.marked_tracks(tracks, id);
+        if !marked_tracks.is_empty() {
+            let context = ActionContext::Tracks(marked_tracks.into_iter().cloned().collect());
+            return handle_action_in_context(action, context, client_pub, data, ui);
+        }
+    }
+
+    handle_action_for_selected_item(action, tracks, data, ui, client_pub)
+}
+
 /// Handle a command for the currently focused context window
 ///
 /// The function will need to determine the focused window then
@@ -256,6 +278,123 @@ fn handle_playlist_modify_command(
     Ok(false)
 }
 
+/// Handle commands that mark tracks of a track table
+fn handle_track_mark_command(
+    command: Command,
+    id: usize,
+    tracks: &[&Track],
+    ui: &mut UIStateGuard,
+) -> bool {
+    match command {
+        Command::ToggleMarkSelectedTrack => {
+            ui.track_marks.toggle(&tracks[id].id);
+            if id + 1 < tracks.len() {
+                ui.current_page_mut().select(id + 1);
+            }
+        }
+        Command::ToggleVisualSelection => {
+            if ui.track_marks.visual_anchor.is_some() {
+                ui.track_marks.commit_visual_selection(tracks, id);
+            } else {
+                ui.track_marks.visual_anchor = Some(id);
+            }
+        }
+        Command::ClearTrackMarks => ui.track_marks.clear(),
+        _ => return false,
+    }
+    true
+}
+
+/// Handle commands on the marked tracks of a track table
+fn handle_command_for_marked_tracks(
+    command: Command,
+    id: usize,
+    modifiable_playlist_id: Option<&PlaylistId<'static>>,
+    client_pub: &flume::Sender<ClientRequest>,
+    tracks: &[Track],
+    data: &DataReadGuard,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let filtered_tracks = ui.search_filtered_items(tracks);
+    let marked_tracks = ui.track_marks.marked_tracks(&filtered_tracks, id);
+    if marked_tracks.is_empty() {
+        return Ok(false);
+    }
+
+    match command {
+        Command::AddSelectedItemToQueue => {
+            client_pub.send(ClientRequest::AddPlayablesToQueue(
+                marked_tracks.iter().map(|t| t.id.clone().into()).collect(),
+            ))?;
+            ui.track_marks.clear();
+        }
+        Command::ShowActionsOnSelectedItem => {
+            let marked_tracks = marked_tracks.into_iter().cloned().collect::<Vec<_>>();
+            let mut actions = command::construct_tracks_actions(&marked_tracks, data);
+            if modifiable_playlist_id.is_some() {
+                actions.push(Action::DeleteFromPlaylist);
+            }
+            ui.popup = Some(PopupState::ActionList(
+                Box::new(ActionListItem::Tracks(marked_tracks, actions)),
+                ListState::default(),
+            ));
+        }
+        Command::MovePlaylistItemUp | Command::MovePlaylistItemDown => {
+            let Some(playlist_id) = modifiable_playlist_id else {
+                return Ok(false);
+            };
+            let up = command == Command::MovePlaylistItemUp;
+
+            // group the marked tracks' positions in the playlist into contiguous ranges,
+            // each of which is moved with a single reorder request
+            let marked_tracks = marked_tracks
+                .into_iter()
+                .map(std::ptr::from_ref)
+                .collect::<HashSet<_>>();
+            let mut ranges: Vec<(usize, usize)> = vec![];
+            for (pos, track) in tracks.iter().enumerate() {
+                if !marked_tracks.contains(&std::ptr::from_ref(track)) {
+                    continue;
+                }
+                match ranges.last_mut() {
+                    Some((start, length)) if *start + *length == pos => *length += 1,
+                    _ => ranges.push((pos, 1)),
+                }
+            }
+
+            let at_boundary = if up {
+                ranges.first().is_some_and(|(start, _)| *start == 0)
+            } else {
+                ranges
+                    .last()
+                    .is_some_and(|(start, length)| start + length == tracks.len())
+            };
+            if at_boundary {
+                return Ok(true);
+            }
+            if !up {
+                ranges.reverse();
+            }
+
+            // the visual selection is relative to the selected track,
+            // so its tracks are marked before moving the selection along with the tracks
+            ui.track_marks.commit_visual_selection(&filtered_tracks, id);
+            client_pub.send(ClientRequest::MovePlaylistItemRanges {
+                playlist_id: playlist_id.clone_static(),
+                ranges,
+                up,
+            })?;
+            if up {
+                ui.current_page_mut().select(id.saturating_sub(1));
+            } else if id + 1 < filtered_tracks.len() {
+                ui.current_page_mut().select(id + 1);
+            }
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
 fn handle_command_for_track_table_window(
     command: Command,
     client_pub: &flume::Sender<ClientRequest>,
@@ -270,22 +409,45 @@ fn handle_command_for_track_table_window(
         return Ok(false);
     }
 
-    if let Some(ContextId::Playlist(ref playlist_id)) = context_id {
-        let modifiable =
-            data.user_data.modifiable_playlist_items(None).iter().any(
+    if handle_track_mark_command(command, id, &filtered_tracks, ui) {
+        return Ok(true);
+    }
+
+    let modifiable_playlist_id = match context_id {
+        Some(ContextId::Playlist(ref playlist_id))
+            if data.user_data.modifiable_playlist_items(None).iter().any(
                 |item| matches!(item, PlaylistFolderItem::Playlist(p) if p.id.eq(playlist_id)),
-            );
-        if modifiable
-            && handle_playlist_modify_command(
-                id,
-                playlist_id,
-                command,
-                client_pub,
-                &filtered_tracks,
-                data,
-                ui,
-            )?
+            ) =>
         {
+            Some(playlist_id)
+        }
+        _ => None,
+    };
+
+    if ui.track_marks.is_active()
+        && handle_command_for_marked_tracks(
+            command,
+            id,
+            modifiable_playlist_id,
+            client_pub,
+            tracks,
+            data,
+            ui,
+        )?
+    {
+        return Ok(true);
+    }
+
+    if let Some(playlist_id) = modifiable_playlist_id {
+        if handle_playlist_modify_command(
+            id,
+            playlist_id,
+            command,
+            client_pub,
+            &filtered_tracks,
+            data,
+            ui,
+        )? {
             return Ok(true);
         }
     }
@@ -0,0 +1,319 @@
+//! Textual commands with arguments, e.g. `volume 40` or `open spotify:playlist:...`.
+//!
+//! The commands are entered in the application's command line (`:`)
+//! or sent to the application by the `exec` CLI command.
+
+use std::str::FromStr;
+
+use anyhow::{Context as _, Result};
+use rspotify::model::TrackId;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config,
+    state::{ContextId, SharedState},
+};
+
+/// the names of the supported commands
+pub const COMMAND_NAMES: [&str; 6] = ["volume", "seek", "search", "open", "theme", "device"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExCommand {
+    /// set the volume percentage, or change it by an offset (`volume +5`)
+    Volume { percent: i8, is_offset: bool },
+    /// seek to a position, or by an offset (`seek -0:10`)
+    Seek { position_ms: i64, is_offset: bool },
+    /// search for a query, which can include field filters (`search artist:foo`)
+    Search(String),
+    /// open a Spotify link: a URI or an `open.spotify.com` URL
+    Open(String),
+    /// switch to a theme by its name
+    Theme(String),
+    /// transfer the playback to a device by its name
+    Device(String),
+}
+
+/// A Spotify item opened by the `open` command
+pub enum OpenTarget {
+    /// a track, which is played
+    Track(TrackId<'static>),
+    /// a context, whose page is opened
+    Context(ContextId),
+}
+
+impl FromStr for ExCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let s = s.strip_prefix(':').unwrap_or(s);
+        let (name, arg) = match s.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (s, ""),
+        };
+        let required_arg = || {
+            if arg.is_empty() {
+                anyhow::bail!("`{name}` command requires an argument");
+            }
+            Ok(arg.to_string())
+        };
+
+        Ok(match name {
+            "volume" => {
+                let (sign, value) = split_sign(arg);
+                let percent = value
+                    .parse::<i8>()
+                    .ok()
+                    .filter(|p| (0..=100).contains(p))
+                    .with_context(|| format!("invalid volume percentage: {arg:?}"))?;
+                Self::Volume {
+                    percent: sign.unwrap_or(1) * percent,
+                    is_offset: sign.is_some(),
+                }
+            }
+            "seek" => {
+                let (sign, value) = split_sign(arg);
+                let position_ms = parse_position_ms(value)
+                    .with_context(|| format!("invalid seek position: {arg:?}"))?;
+                Self::Seek {
+                    position_ms: i64::from(sign.unwrap_or(1)) * position_ms,
+                    is_offset: sign.is_some(),
+                }
+            }
+            "search" => Self::Search(required_arg()?),
+            "open" => {
+                let link = required_arg()?;
+                // validate the link when parsing the command
+                OpenTarget::from_link(&link)?;
+                Self::Open(link)
+            }
+            "theme" => Self::Theme(required_arg()?),
+            "device" => Self::Device(required_arg()?),
+            "" => anyhow::bail!("empty command"),
+            _ => anyhow::bail!(
+                "unknown command `{name}`, expect one of: {}",
+                COMMAND_NAMES.join(", ")
+            ),
+        })
+    }
+}
+
+/// Split the sign of a relative value (`+5` or `-5`) from the value.
+/// The returned sign is `None` for an absolute value.
+fn split_sign(arg: &str) -> (Option<i8>, &str) {
+    if let Some(value) = arg.strip_prefix('+') {
+        (Some(1), value)
+    } else if let Some(value) = arg.strip_prefix('-') {
+        (Some(-1), value)
+    } else {
+        (None, arg)
+    }
+}
+
+/// Parse a position of format `[[hours:]minutes:]seconds` into milliseconds
+fn parse_position_ms(value: &str) -> Option<i64> {
+    let parts = value.split(':').collect::<Vec<_>>();
+    if parts.len() > 3 {
+        return None;
+    }
+    let mut secs: u32 = 0;
+    for part in parts {
+        secs = secs.checked_mul(60)?.checked_add(part.parse().ok()?)?;
+    }
+    Some(i64::from(secs) * 1000)
+}
+
+impl OpenTarget {
+    /// Parse a Spotify link, which is either a URI (`spotify:playlist:{id}`)
+    /// or a URL (`https://open.spotify.com/playlist/{id}`)
+    pub fn from_link(link: &str) -> Result<Self> {
+        let uri = match link.strip_prefix("https://open.spotify.com/") {
+            Some(path) => {
+                // a URL's path may have a locale prefix (`intl-fr/playlist/{id}`)
+                // and a query string (`?si=...`)
+                let path = path.split(['?', '#']).next().unwrap_or_default();
+                let parts = path
+                    .split('/')
+                    .filter(|p| !p.is_empty())
+                    .collect::<Vec<_>>();
+                match parts[..] {
+                    [.., typ, id] => format!("spotify:{typ}:{id}"),
+                    _ => anyhow::bail!("invalid Spotify URL: {link}"),
+                }
+            }
+            None => link.to_string(),
+        };
+
+        if uri.starts_with("spotify:track:") {
+            Ok(Self::Track(TrackId::from_uri(&uri)?.into_static()))
+        } else {
+            Ok(Self::Context(ContextId::from_uri(&uri)?))
+        }
+    }
+}
+
+/// Find a theme by its name (case-insensitive)
+pub fn find_theme(name: &str) -> Result<config::Theme> {
+    config::get_config()
+        .theme_config
+        .themes
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name))
+        .cloned()
+        .with_context(|| format!("no theme with name={name} found"))
+}
+
+/// Get the completions of a partially entered command
+pub fn complete(input: &str, state: &SharedState) -> Vec<String> {
+    let Some((name, arg)) = input.split_once(' ') else {
+        return COMMAND_NAMES
+            .iter()
+            .filter(|n| n.starts_with(input))
+            .map(|n| format!("{n} "))
+            .collect();
+    };
+
+    let candidates = match name {
+        "theme" => config::get_config()
+            .theme_config
+            .themes
+            .iter()
+            .map(|t| t.name.clone())
+            .collect(),
+        "device" => state
+            .player
+            .read()
+            .devices
+            .iter()
+            .map(|d| d.name.clone())
+            .collect(),
+        _ => vec![],
+    };
+    let arg = arg.trim_start().to_lowercase();
+    candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().starts_with(&arg))
+        .map(|c| format!("{name} {c}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_position_ms, ExCommand, OpenTarget};
+    use crate::state::ContextId;
+
+    const ID: &str = "4uLU6hMCjMI75M1A2tKUQC";
+
+    #[test]
+    fn parse_volume_command() {
+        assert_eq!(
+            "volume 40".parse::<ExCommand>().unwrap(),
+            ExCommand::Volume {
+                percent: 40,
+                is_offset: false
+            }
+        );
+        assert_eq!(
+            "volume +5".parse::<ExCommand>().unwrap(),
+            ExCommand::Volume {
+                percent: 5,
+                is_offset: true
+            }
+        );
+        assert_eq!(
+            ":volume -5".parse::<ExCommand>().unwrap(),
+            ExCommand::Volume {
+                percent: -5,
+                is_offset: true
+            }
+        );
+        assert!("volume 101".parse::<ExCommand>().is_err());
+        assert!("volume".parse::<ExCommand>().is_err());
+    }
+
+    #[test]
+    fn parse_seek_command() {
+        assert_eq!(
+            "seek 1:23".parse::<ExCommand>().unwrap(),
+            ExCommand::Seek {
+                position_ms: 83_000,
+                is_offset: false
+            }
+        );
+        assert_eq!(
+            "seek -0:10".parse::<ExCommand>().unwrap(),
+            ExCommand::Seek {
+                position_ms: -10_000,
+                is_offset: true
+            }
+        );
+        assert_eq!(
+            "seek +30".parse::<ExCommand>().unwrap(),
+            ExCommand::Seek {
+                position_ms: 30_000,
+                is_offset: true
+            }
+        );
+        assert!("seek 1:xx".parse::<ExCommand>().is_err());
+    }
+
+    #[test]
+    fn parse_other_commands() {
+        assert_eq!(
+            "search  artist:foo bar ".parse::<ExCommand>().unwrap(),
+            ExCommand::Search("artist:foo bar".to_string())
+        );
+        assert_eq!(
+            "device Kitchen".parse::<ExCommand>().unwrap(),
+            ExCommand::Device("Kitchen".to_string())
+        );
+        assert!("open not-a-link".parse::<ExCommand>().is_err());
+        assert!("theme".parse::<ExCommand>().is_err());
+        assert!("unknown 1".parse::<ExCommand>().is_err());
+        assert!("".parse::<ExCommand>().is_err());
+    }
+
+    #[test]
+    fn parse_position() {
+        assert_eq!(parse_position_ms("45"), Some(45_000));
+        assert_eq!(parse_position_ms("1:23"), Some(83_000));
+        assert_eq!(parse_position_ms("1:02:03"), Some(3_723_000));
+        assert_eq!(parse_position_ms("1:2:3:4"), None);
+        assert_eq!(parse_position_ms("-1"), None);
+        assert_eq!(parse_position_ms(""), None);
+        // overflowing positions are rejected
+        assert_eq!(parse_position_ms("4294967295:0"), None);
+        assert_eq!(parse_position_ms("71582789:0:0"), None);
+    }
+
+    #[test]
+    fn parse_open_link() {
+        let uri = format!("spotify:track:{ID}");
+        assert!(matches!(
+            OpenTarget::from_link(&uri).unwrap(),
+            OpenTarget::Track(id) if id.to_string() == uri
+        ));
+
+        let url = format!("https://open.spotify.com/playlist/{ID}?si=abc");
+        assert!(matches!(
+            OpenTarget::from_link(&url).unwrap(),
+            OpenTarget::Context(ContextId::Playlist(_))
+        ));
+
+        // a URL with a locale prefix
+        let url = format!("https://open.spotify.com/intl-fr/album/{ID}");
+        assert!(matches!(
+            OpenTarget::from_link(&url).unwrap(),
+            OpenTarget::Context(ContextId::Album(_))
+        ));
+        let url = format!("https://open.spotify.com/intl-pt/track/{ID}#x");
+        assert!(matches!(
+            OpenTarget::from_link(&url).unwrap(),
+            OpenTarget::Track(_)
+        ));
+
+        assert!(OpenTarget::from_link("https://open.spotify.com/").is_err());
+        assert!(OpenTarget::from_link(&format!("spotify:episode:{ID}")).is_err());
+        assert!(OpenTarget::from_link("spotify:artist:invalid-id").is_err());
+    }
+}
@@ -3,7 +3,10 @@ mod cli;
 mod client;
 mod command;
 mod config;
+#[cfg(feature = "streaming")]
+mod dsp;
 mod event;
+mod ex_command;
 mod key;
 #[cfg(feature = "media-control")]
 mod media_control;
@@ -117,10 +120,21 @@ async fn start_app(state: &state::SharedState) -> Result<()> {
     // create a Spotify API client
     let auth_config = auth::AuthConfig::new(configs)?;
     let client = client::Client::new(auth_config);
-    client
-        .new_session(Some(state), true)
-        .await
-        .context("initialize new Spotify session")?;
+    if let Err(err) = client.new_session(Some(state), true).await {
+        // pinned tracks can still be played from the audio cache without a connection to Spotify
+        let has_pinned_tracks = cfg!(feature = "streaming")
+            && state
+                .data
+                .read()
+                .user_data
+                .pinned_contexts
+                .values()
+                .any(|c| !c.tracks.is_empty());
+        if !has_pinned_tracks {
+            return Err(err.context("initialize new Spotify session"));
+        }
+        tracing::warn!("Failed to initialize a new Spotify session, starting offline: {err:#}");
+    }
 
     // initialize Spotify-related stuff
     init_spotify(&client_pub, &client, state).context("Failed to initialize the Spotify data")?;
@@ -146,6 +160,34 @@ async fn start_app(state: &state::SharedState) -> Result<()> {
         }
     }));
 
+    // client Unix socket task (for handling CLI commands)
+    #[cfg(unix)]
+    tasks.push(tokio::task::spawn({
+        let client = client.clone();
+        let state = state.clone();
+        async move {
+            let path = cli::unix_socket_path();
+            if let Err(err) = cli::start_unix_socket(client, &path, Some(state)).await {
+                tracing::warn!(
+                    "Failed to create a client Unix socket for handling CLI commands: {err:#}"
+                );
+            }
+        }
+    }));
+
+    // HTTP API server task
+    if let Some(address) = &configs.app_config.http_api_address {
+        tasks.push(tokio::task::spawn({
+            let client = client.clone();
+            let state = state.clone();
+            async move {
+                if let Err(err) = cli::start_http_server(client, address, Some(state)).await {
+                    tracing::warn!("Failed to start the HTTP API server: {err:#}");
+                }
+            }
+        }));
+    }
+
     // client event handler task
     tasks.push(tokio::task::spawn({
         let state = state.clone();
@@ -154,6 +196,15 @@ async fn start_app(state: &state::SharedState) -> Result<()> {
         }
     }));
 
+    // scheduler task of the sleep timer and alarms
+    tasks.push(tokio::task::spawn({
+        let state = state.clone();
+        let client_pub = client_pub.clone();
+        async move {
+            client::start_scheduler(state, client_pub).await;
+        }
+    }));
+
     // player event watcher task
     tasks.push(tokio::task::spawn({
         let state = state.clone();
@@ -281,7 +332,13 @@ fn main() -> Result<()> {
                     }
 
                     tracing::info!("Starting the application as a daemon...");
-                    let daemonize = daemonize::Daemonize::new();
+                    let mut daemonize = daemonize::Daemonize::new();
+                    // the `pipe` audio backend without an output device writes audio to stdout,
+                    // which would be redirected to `/dev/null` otherwise
+                    let device = &config::get_config().app_config.device;
+                    if device.backend.as_deref() == Some("pipe") && device.output_device.is_none() {
+                        daemonize = daemonize.stdout(daemonize::Stdio::keep());
+                    }
                     daemonize.start()?;
                 }
             }
@@ -1,7 +1,10 @@
 use std::io::{BufReader, BufWriter};
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::sync::LazyLock;
 
 use super::model::{
@@ -20,12 +23,38 @@ pub enum FileCacheKey {
     SavedShows,
     SavedAlbums,
     SavedTracks,
+    LyricsOffsets,
+    PinnedContexts,
+    CommandLineHistory,
+}
+
+impl FileCacheKey {
+    /// Whether the cache stores the user's own state, which can't be fetched again from Spotify
+    pub fn is_user_state(self) -> bool {
+        matches!(
+            self,
+            Self::LyricsOffsets | Self::PinnedContexts | Self::CommandLineHistory
+        )
+    }
+
+    /// Get the path of the cache's file in the cache folder
+    pub fn file_path(self, cache_folder: &Path) -> PathBuf {
+        cache_folder.join(format!("{self:?}_cache.json"))
+    }
 }
 
 /// default time-to-live cache duration
 pub static TTL_CACHE_DURATION: LazyLock<std::time::Duration> =
     LazyLock::new(|| std::time::Duration::from_secs(60 * 60));
 
+/// time-to-live duration (in days) of a track's lyrics persisted in the lyrics file cache
+const LYRICS_FILE_CACHE_TTL_IN_DAYS: u64 = 30;
+
+/// time-to-live duration (in days) of a "no lyrics found" result persisted in the lyrics file cache
+const NO_LYRICS_FILE_CACHE_TTL_IN_DAYS: u64 = 1;
+
+pub const LYRICS_CACHE_FOLDER: &str = "lyrics";
+
 /// the application's data
 pub struct AppData {
     pub user_data: UserData,
@@ -43,6 +72,54 @@ pub struct UserData {
     pub saved_shows: Vec<Show>,
     pub saved_albums: Vec<Album>,
     pub saved_tracks: HashMap<String, Track>,
+    /// user-adjusted timing offsets (in milliseconds) of tracks' lyrics, keyed by track's URI
+    pub lyrics_offsets: HashMap<String, i64>,
+    /// playlists and albums pinned for offline listening, keyed by context's URI
+    pub pinned_contexts: HashMap<String, PinnedContext>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a playlist or an album pinned for offline listening, whose tracks' audio files
+/// are prefetched into the audio cache
+pub struct PinnedContext {
+    pub name: String,
+    /// the number of tracks to prefetch
+    pub total: usize,
+    /// the prefetched tracks in the context's order
+    #[serde(default)]
+    pub tracks: Vec<PinnedTrack>,
+    /// the number of tracks failed to be prefetched
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a track prefetched into the audio cache, which can be played without a connection to Spotify
+pub struct PinnedTrack {
+    pub track: Track,
+    /// id (in base16) of the track's audio file
+    pub file_id: String,
+    /// key (in base16) to decrypt the track's audio file
+    pub key: String,
+    /// whether the audio file is in the Ogg Vorbis format, otherwise in the MP3 format
+    pub is_ogg_vorbis: bool,
+}
+
+/// Get the path of an audio file (identified by its id in base16) in the audio cache,
+/// which follows `librespot`'s audio cache layout
+pub fn audio_cache_file_path(audio_folder: &Path, file_id: &str) -> PathBuf {
+    audio_folder.join(&file_id[0..2]).join(&file_id[2..])
+}
+
+impl PinnedContext {
+    /// Get the pinning progress's description
+    pub fn progress(&self) -> String {
+        let progress = format!("Pinned {}/{}", self.tracks.len(), self.total);
+        if self.failed > 0 {
+            format!("{progress} ({} failed)", self.failed)
+        } else {
+            progress
+        }
+    }
 }
 
 /// the application's in-memory caches
@@ -137,6 +214,10 @@ impl UserData {
                 .unwrap_or_default(),
             saved_tracks: load_data_from_file_cache(FileCacheKey::SavedTracks, cache_folder)
                 .unwrap_or_default(),
+            lyrics_offsets: load_data_from_file_cache(FileCacheKey::LyricsOffsets, cache_folder)
+                .unwrap_or_default(),
+            pinned_contexts: load_data_from_file_cache(FileCacheKey::PinnedContexts, cache_folder)
+                .unwrap_or_default(),
         }
     }
 
@@ -186,6 +267,15 @@ impl UserData {
         self.saved_tracks.contains_key(&track.id.uri())
     }
 
+    /// Get the timing offset of a track's lyrics
+    pub fn lyrics_offset(&self, track_uri: &str) -> chrono::Duration {
+        self.lyrics_offsets
+            .get(track_uri)
+            .map_or(chrono::Duration::zero(), |ms| {
+                chrono::Duration::milliseconds(*ms)
+            })
+    }
+
     /// Check if a playlist is followed
     pub fn is_followed_playlist(&self, playlist: &Playlist) -> bool {
         self.playlists.iter().any(|x| match x {
@@ -200,7 +290,7 @@ pub fn store_data_into_file_cache<T: Serialize>(
     cache_folder: &Path,
     data: &T,
 ) -> std::io::Result<()> {
-    let path = cache_folder.join(format!("{key:?}_cache.json"));
+    let path = key.file_path(cache_folder);
     let f = BufWriter::new(std::fs::File::create(path)?);
     serde_json::to_writer(f, data)?;
     Ok(())
@@ -210,7 +300,7 @@ pub fn load_data_from_file_cache<T>(key: FileCacheKey, cache_folder: &Path) -> O
 where
     T: DeserializeOwned,
 {
-    let path = cache_folder.join(format!("{key:?}_cache.json"));
+    let path = key.file_path(cache_folder);
     if path.exists() {
         tracing::info!("Loading {key:?} data from {}...", path.display());
         let f = BufReader::new(std::fs::File::open(path).expect("path exists"));
@@ -228,3 +318,111 @@ where
         None
     }
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+/// A track's lyrics lookup result persisted in the lyrics file cache
+pub struct LyricsCacheEntry {
+    /// the time (in seconds since the Unix epoch) the lyrics were fetched
+    pub fetched_at: u64,
+    /// the track's lyrics, `None` if no lyrics was found
+    pub lyrics: Option<Lyrics>,
+}
+
+impl LyricsCacheEntry {
+    fn is_expired(&self, now: u64) -> bool {
+        let ttl_in_days = if self.lyrics.is_some() {
+            LYRICS_FILE_CACHE_TTL_IN_DAYS
+        } else {
+            NO_LYRICS_FILE_CACHE_TTL_IN_DAYS
+        };
+        self.fetched_at + ttl_in_days * 24 * 60 * 60 < now
+    }
+}
+
+fn unix_timestamp_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn lyrics_file_cache_path(cache_folder: &Path, track_id: &str) -> std::path::PathBuf {
+    cache_folder
+        .join(LYRICS_CACHE_FOLDER)
+        .join(format!("{track_id}.json"))
+}
+
+/// Store a track's lyrics lookup result into the lyrics file cache.
+/// A `None` value indicates that no lyrics was found for the track.
+pub fn store_lyrics_into_file_cache(
+    cache_folder: &Path,
+    track_id: &str,
+    lyrics: Option<Lyrics>,
+) -> std::io::Result<()> {
+    let entry = LyricsCacheEntry {
+        fetched_at: unix_timestamp_now(),
+        lyrics,
+    };
+
+    let path = lyrics_file_cache_path(cache_folder, track_id);
+    std::fs::create_dir_all(cache_folder.join(LYRICS_CACHE_FOLDER))?;
+    let f = BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer(f, &entry)?;
+    Ok(())
+}
+
+/// Load a track's lyrics lookup result from the lyrics file cache.
+/// Returns `None` if the track has no cached result or the cached result is expired.
+pub fn load_lyrics_from_file_cache(
+    cache_folder: &Path,
+    track_id: &str,
+) -> Option<LyricsCacheEntry> {
+    let path = lyrics_file_cache_path(cache_folder, track_id);
+    let f = BufReader::new(std::fs::File::open(&path).ok()?);
+    let entry = match serde_json::from_reader::<_, LyricsCacheEntry>(f) {
+        Ok(entry) => entry,
+        Err(err) => {
+            tracing::error!(
+                "Failed to load lyrics data from {}: {err:#}",
+                path.display()
+            );
+            return None;
+        }
+    };
+
+    if entry.is_expired(unix_timestamp_now()) {
+        None
+    } else {
+        Some(entry)
+    }
+}
+
+/// Remove expired (or invalid) entries from the lyrics file cache,
+/// returns the number of removed entries
+pub fn prune_lyrics_file_cache(cache_folder: &Path) -> std::io::Result<usize> {
+    let folder = cache_folder.join(LYRICS_CACHE_FOLDER);
+    if !folder.exists() {
+        return Ok(0);
+    }
+
+    let now = unix_timestamp_now();
+    let mut removed = 0;
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+        // only cache entries (files) are removed
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let expired = match std::fs::read(&path) {
+            Ok(content) => serde_json::from_slice::<LyricsCacheEntry>(&content)
+                .map_or(true, |entry| entry.is_expired(now)),
+            Err(_) => true,
+        };
+        if expired {
+            std::fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
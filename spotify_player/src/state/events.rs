@@ -0,0 +1,245 @@
+use rspotify::model::PlayableItem;
+use rspotify::prelude::Id;
+use serde::Serialize;
+
+use super::{Mutex, State};
+
+/// the maximum number of events buffered for a slow subscriber
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// the minimum difference (in milliseconds) between the estimated and the actual
+/// playback's progress for the progress change to be considered a seek
+const SEEK_THRESHOLD_MS: i64 = 2000;
+
+/// An event describing a change of the player's state, streamed to subscribers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum PlayerStateEvent {
+    TrackChanged {
+        id: String,
+        name: String,
+        artists: Vec<String>,
+        duration_ms: i64,
+    },
+    Playing {
+        id: String,
+        position_ms: i64,
+    },
+    Paused {
+        id: String,
+        position_ms: i64,
+    },
+    Seeked {
+        id: String,
+        position_ms: i64,
+    },
+    #[cfg(feature = "streaming")]
+    EndOfTrack {
+        id: String,
+    },
+    VolumeChanged {
+        volume: u32,
+    },
+    QueueChanged {
+        queue: Vec<String>,
+    },
+    DeviceChanged {
+        id: Option<String>,
+        name: String,
+    },
+    LikedChanged {
+        id: String,
+        liked: bool,
+    },
+}
+
+/// A channel broadcasting player state events to subscribers
+pub struct PlayerEvents {
+    sender: tokio::sync::broadcast::Sender<PlayerStateEvent>,
+    /// the player's state snapshot taken when the player state events were last derived
+    snapshot: Mutex<Option<PlayerStateSnapshot>>,
+}
+
+impl PlayerEvents {
+    pub fn new() -> Self {
+        Self {
+            sender: tokio::sync::broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            snapshot: Mutex::new(None),
+        }
+    }
+
+    /// Subscribe to new player state events
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<PlayerStateEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publish a player state event to all subscribers
+    pub fn publish(&self, event: PlayerStateEvent) {
+        // sending only fails if there is no subscriber, which is expected
+        self.sender.send(event).unwrap_or_default();
+    }
+}
+
+impl State {
+    /// Publish the player state events derived from the changes of the player's state
+    /// since the events were last derived.
+    ///
+    /// This is called whenever the player's state is updated, either by the integrated
+    /// player's events or by the Spotify API, and periodically to catch other changes.
+    /// `seeked` is set if the playback is known to be seeked, otherwise seeks are
+    /// estimated from the playback's progress.
+    pub fn publish_player_state_changes(&self, seeked: bool) {
+        let snapshot = PlayerStateSnapshot::new(self);
+        let mut prev = self.events.snapshot.lock();
+        if let Some(prev) = prev.as_ref() {
+            for event in snapshot.events_since(prev, seeked) {
+                self.events.publish(event);
+            }
+        }
+        *prev = Some(snapshot);
+    }
+}
+
+/// A snapshot of the player's state, used to derive player state events
+/// by comparing two consecutive snapshots
+struct PlayerStateSnapshot {
+    item: Option<(String, String, Vec<String>, i64)>,
+    is_playing: bool,
+    progress_ms: i64,
+    taken_at: std::time::Instant,
+    volume: Option<u32>,
+    queue: Option<Vec<String>>,
+    device: Option<(Option<String>, String)>,
+    liked: Option<bool>,
+}
+
+impl PlayerStateSnapshot {
+    fn new(state: &State) -> Self {
+        let player = state.player.read();
+
+        let item = player.currently_playing().and_then(|item| match item {
+            PlayableItem::Track(track) => Some((
+                track.id.as_ref()?.uri(),
+                track.name.clone(),
+                track.artists.iter().map(|a| a.name.clone()).collect(),
+                track.duration.num_milliseconds(),
+            )),
+            PlayableItem::Episode(episode) => Some((
+                episode.id.uri(),
+                episode.name.clone(),
+                vec![],
+                episode.duration.num_milliseconds(),
+            )),
+        });
+        let liked = match player.currently_playing() {
+            Some(PlayableItem::Track(track)) => track.id.as_ref().map(|id| {
+                state
+                    .data
+                    .read()
+                    .user_data
+                    .saved_tracks
+                    .contains_key(&id.uri())
+            }),
+            _ => None,
+        };
+        let queue = player.queue.as_ref().map(|q| {
+            q.queue
+                .iter()
+                .filter_map(|item| item.id().map(|id| id.uri()))
+                .collect()
+        });
+
+        Self {
+            item,
+            is_playing: player
+                .buffered_playback
+                .as_ref()
+                .is_some_and(|p| p.is_playing),
+            progress_ms: player
+                .playback_progress()
+                .map_or(0, |p| p.num_milliseconds()),
+            taken_at: std::time::Instant::now(),
+            volume: player.buffered_playback.as_ref().and_then(|p| p.volume),
+            queue,
+            device: player
+                .buffered_playback
+                .as_ref()
+                .map(|p| (p.device_id.clone(), p.device_name.clone())),
+            liked,
+        }
+    }
+
+    /// Get the events that happened between the `prev` snapshot and this snapshot
+    fn events_since(&self, prev: &Self, seeked: bool) -> Vec<PlayerStateEvent> {
+        let mut events = vec![];
+
+        let Some((id, name, artists, duration_ms)) = self.item.clone() else {
+            return events;
+        };
+        let position_ms = self.progress_ms;
+
+        let track_changed = prev.item.as_ref().is_none_or(|item| item.0 != id);
+        if track_changed {
+            events.push(PlayerStateEvent::TrackChanged {
+                id: id.clone(),
+                name,
+                artists,
+                duration_ms,
+            });
+        }
+        if track_changed || self.is_playing != prev.is_playing {
+            events.push(if self.is_playing {
+                PlayerStateEvent::Playing {
+                    id: id.clone(),
+                    position_ms,
+                }
+            } else {
+                PlayerStateEvent::Paused {
+                    id: id.clone(),
+                    position_ms,
+                }
+            });
+        } else {
+            // estimate the current progress from the previous snapshot to detect a seek
+            let elapsed_ms = if prev.is_playing {
+                self.taken_at.duration_since(prev.taken_at).as_millis() as i64
+            } else {
+                0
+            };
+            if seeked || (prev.progress_ms + elapsed_ms - position_ms).abs() > SEEK_THRESHOLD_MS {
+                events.push(PlayerStateEvent::Seeked {
+                    id: id.clone(),
+                    position_ms,
+                });
+            }
+        }
+
+        if let Some(volume) = self.volume {
+            if prev.volume != Some(volume) {
+                events.push(PlayerStateEvent::VolumeChanged { volume });
+            }
+        }
+        if let Some(queue) = &self.queue {
+            if prev.queue.as_ref() != Some(queue) {
+                events.push(PlayerStateEvent::QueueChanged {
+                    queue: queue.clone(),
+                });
+            }
+        }
+        if let Some((device_id, device_name)) = &self.device {
+            if prev.device.as_ref().is_none_or(|d| d.0 != *device_id) {
+                events.push(PlayerStateEvent::DeviceChanged {
+                    id: device_id.clone(),
+                    name: device_name.clone(),
+                });
+            }
+        }
+        if let Some(liked) = self.liked {
+            if track_changed || prev.liked != Some(liked) {
+                events.push(PlayerStateEvent::LikedChanged { id, liked });
+            }
+        }
+
+        events
+    }
+}
@@ -1,11 +1,13 @@
 mod constant;
 mod data;
+mod events;
 mod model;
 mod player;
 mod ui;
 
 pub use constant::*;
 pub use data::*;
+pub use events::*;
 pub use model::*;
 pub use player::*;
 pub use ui::*;
@@ -22,6 +24,15 @@ pub struct State {
     pub ui: Mutex<UIState>,
     pub player: RwLock<PlayerState>,
     pub data: RwLock<AppData>,
+    pub events: PlayerEvents,
+    /// the name of the integrated player's active DSP preset
+    #[cfg(feature = "streaming")]
+    pub dsp_preset: RwLock<Option<String>>,
+    /// the integrated player's most recent audio samples, used by the visualizer page
+    #[cfg(feature = "streaming")]
+    pub audio_samples: Mutex<std::collections::VecDeque<f64>>,
+    #[cfg(feature = "streaming")]
+    pub offline_player: OfflinePlayerState,
 
     pub is_daemon: bool,
 }
@@ -40,8 +51,20 @@ impl State {
 
         Self {
             ui: Mutex::new(ui),
-            player: RwLock::new(PlayerState::default()),
+            player: RwLock::new(PlayerState {
+                alarms: configs.app_config.alarms.clone(),
+                ..PlayerState::default()
+            }),
             data: RwLock::new(app_data),
+            events: PlayerEvents::new(),
+            #[cfg(feature = "streaming")]
+            dsp_preset: RwLock::new(configs.app_config.device.dsp_preset.clone()),
+            #[cfg(feature = "streaming")]
+            audio_samples: Mutex::new(std::collections::VecDeque::with_capacity(
+                crate::dsp::VISUALIZER_SAMPLES,
+            )),
+            #[cfg(feature = "streaming")]
+            offline_player: OfflinePlayerState::new(configs.app_config.device.volume),
             is_daemon,
         }
     }
@@ -1,3 +1,4 @@
+use crate::config::LyricsProvider;
 use crate::ui::utils::to_bidi_string;
 use crate::utils::map_join;
 use html_escape::decode_html_entities;
@@ -297,6 +298,18 @@ fn play_time(tracks: &[Track]) -> String {
 }
 
 impl ContextId {
+    /// Parse a playlist, album, artist or show URI into a context ID
+    pub fn from_uri(uri: &str) -> anyhow::Result<Self> {
+        let uri = crate::utils::parse_uri(uri);
+        Ok(match uri.split(':').nth(1) {
+            Some("playlist") => Self::Playlist(PlaylistId::from_uri(&uri)?.into_static()),
+            Some("album") => Self::Album(AlbumId::from_uri(&uri)?.into_static()),
+            Some("artist") => Self::Artist(ArtistId::from_uri(&uri)?.into_static()),
+            Some("show") => Self::Show(ShowId::from_uri(&uri)?.into_static()),
+            _ => anyhow::bail!("unsupported context URI: {uri}"),
+        })
+    }
+
     pub fn uri(&self) -> String {
         match self {
             Self::Album(id) => id.uri(),
@@ -740,14 +753,87 @@ impl PlaybackMetadata {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Lyrics {
-    /// Timestamped lines
+    /// Timestamped lines, whose text is in logical order and only reordered for display
+    #[serde(with = "timestamped_lines")]
     pub lines: Vec<(chrono::Duration, String)>,
+    /// Whether the lines' timestamps are meaningful.
+    /// Unsynced lyrics have all their timestamps set to zero.
+    pub synced: bool,
+    /// The provider the lyrics were retrieved from
+    pub provider: LyricsProvider,
+    /// The lines' translations (or romanizations) from the `lyrics_translation_command`,
+    /// one for each line
+    #[serde(skip)]
+    pub translations: Option<Vec<String>>,
+}
+
+impl Lyrics {
+    /// Construct lyrics from the result of a `lyric_finder` lyrics provider
+    pub fn from_lyric_finder(lyrics: lyric_finder::Lyrics, provider: LyricsProvider) -> Self {
+        Self {
+            lines: lyrics
+                .lines
+                .into_iter()
+                .map(|(t, line)| (chrono::Duration::from_std(t).unwrap_or_default(), line))
+                .collect(),
+            synced: lyrics.synced,
+            provider,
+            translations: None,
+        }
+    }
+}
+
+impl Lyrics {
+    /// Get the id of the last line whose timestamp is not after the playback's `progress`
+    pub fn current_line_id(&self, progress: chrono::Duration) -> Option<usize> {
+        self.lines.iter().rposition(|(t, _)| *t <= progress)
+    }
+
+    /// Get the input of the `lyrics_translation_command`, which receives
+    /// the lyrics' lines from stdin, one line per input line
+    pub fn translation_input(&self) -> String {
+        self.lines
+            .iter()
+            .map(|(_, line)| line.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Set the lyrics' translations from the `lyrics_translation_command`'s `output`,
+    /// which has one translated line for each input line
+    pub fn set_translations(&mut self, output: &str) {
+        let mut translations = output.lines().map(String::from).collect::<Vec<_>>();
+        if translations.len() != self.lines.len() {
+            tracing::warn!(
+                "Lyrics translation command returned {} lines, expected {} lines",
+                translations.len(),
+                self.lines.len()
+            );
+        }
+        // keep the translations aligned with the lyrics' lines
+        translations.resize(self.lines.len(), String::new());
+        self.translations = Some(translations);
+    }
+
+    /// Format the lyrics into the content of an `.lrc` file
+    pub fn to_lrc(&self, track: &str, artists: &str) -> String {
+        let lyrics = lyric_finder::Lyrics {
+            lines: self
+                .lines
+                .iter()
+                .map(|(t, line)| (t.to_std().unwrap_or_default(), line.clone()))
+                .collect(),
+            synced: self.synced,
+        };
+        lyric_finder::lrc::format(&lyrics, &[("ar", artists), ("ti", track)])
+    }
 }
 
 impl From<librespot_metadata::lyrics::Lyrics> for Lyrics {
     fn from(value: librespot_metadata::lyrics::Lyrics) -> Self {
+        let synced = value.lyrics.sync_type == librespot_metadata::lyrics::SyncType::LineSynced;
         let mut lines = value
             .lyrics
             .lines
@@ -757,10 +843,41 @@ impl From<librespot_metadata::lyrics::Lyrics> for Lyrics {
                     l.start_time_ms.parse::<i64>().expect("invalid number"),
                 );
 
-                (t, to_bidi_string(&l.words))
+                (t, l.words)
             })
             .collect::<Vec<_>>();
         lines.sort_by_key(|l| l.0);
-        Self { lines }
+        Self {
+            lines,
+            synced,
+            provider: LyricsProvider::Spotify,
+            translations: None,
+        }
+    }
+}
+
+/// (De)serialize lyrics' timestamped lines with timestamps in milliseconds
+mod timestamped_lines {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S>(lines: &[(chrono::Duration, String)], s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        lines
+            .iter()
+            .map(|(t, line)| (t.num_milliseconds(), line))
+            .collect::<Vec<_>>()
+            .serialize(s)
+    }
+
+    pub fn deserialize<'de, D>(d: D) -> Result<Vec<(chrono::Duration, String)>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(i64, String)>::deserialize(d)?
+            .into_iter()
+            .map(|(t, line)| (chrono::Duration::milliseconds(t), line))
+            .collect())
     }
 }
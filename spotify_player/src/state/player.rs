@@ -1,4 +1,13 @@
-use super::model::{AlbumId, ArtistId, ContextId, Device, PlaybackMetadata, PlaylistId, ShowId};
+#[cfg(feature = "streaming")]
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::data::AppData;
+use super::model::{
+    AlbumId, ArtistId, ContextId, Device, Id, PlaybackMetadata, PlaylistId, ShowId,
+};
 
 /// Player state
 #[derive(Default, Debug)]
@@ -12,6 +21,150 @@ pub struct PlayerState {
     pub buffered_playback: Option<PlaybackMetadata>,
 
     pub queue: Option<rspotify::model::CurrentUserQueue>,
+
+    pub sleep_timer: Option<SleepTimer>,
+    /// alarms scheduled to start a context's playback
+    pub alarms: Vec<crate::config::Alarm>,
+}
+
+/// The state of the offline player, which is shared with the offline player's thread
+#[cfg(feature = "streaming")]
+#[derive(Debug)]
+pub struct OfflinePlayerState {
+    /// whether the offline player is running
+    pub is_running: AtomicBool,
+    /// whether the offline player is playing
+    pub is_playing: AtomicBool,
+    /// the offline player's volume (in percentage), which is read for each decoded audio packet
+    pub volume: AtomicU8,
+}
+
+#[cfg(feature = "streaming")]
+impl OfflinePlayerState {
+    pub fn new(volume: u8) -> Self {
+        Self {
+            is_running: AtomicBool::new(false),
+            is_playing: AtomicBool::new(false),
+            volume: AtomicU8::new(volume.min(100)),
+        }
+    }
+
+    /// Get the offline player's volume if the offline player is running
+    pub fn running_volume(&self) -> Option<u8> {
+        self.is_running
+            .load(Ordering::Relaxed)
+            .then(|| self.volume.load(Ordering::Relaxed))
+    }
+
+    /// Get the offline player's volume if the offline player is playing
+    pub fn playing_volume(&self) -> Option<u8> {
+        self.running_volume()
+            .filter(|_| self.is_playing.load(Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// A mode of the sleep timer, which specifies when the playback is paused
+pub enum SleepTimerMode {
+    After { minutes: u64 },
+    EndOfTrack,
+    EndOfContext,
+}
+
+#[derive(Debug, Clone)]
+/// A sleep timer pausing the playback when it ends
+pub struct SleepTimer {
+    pub mode: SleepTimerMode,
+    end: SleepTimerEnd,
+    /// the duration of fading out the volume before pausing the playback
+    pub fade_out: Duration,
+    /// the playback's volume before fading out, which is restored after pausing the playback
+    pub volume_before_fade_out: Option<u8>,
+}
+
+#[derive(Debug, Clone)]
+enum SleepTimerEnd {
+    Time(Instant),
+    /// the end of a track, which is identified by the URI of the playing item when starting the timer
+    EndOfTrack(Option<String>),
+    /// the end of a context, which is the playing context when starting the timer
+    EndOfContext(Option<ContextId>),
+}
+
+impl std::fmt::Display for SleepTimerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::After { minutes } => write!(f, "{minutes} minutes"),
+            Self::EndOfTrack => write!(f, "end of track"),
+            Self::EndOfContext => write!(f, "end of context"),
+        }
+    }
+}
+
+impl SleepTimer {
+    pub fn new(mode: SleepTimerMode, fade_out: Duration, player: &PlayerState) -> Self {
+        let end = match mode {
+            SleepTimerMode::After { minutes } => {
+                SleepTimerEnd::Time(Instant::now() + Duration::from_secs(minutes * 60))
+            }
+            SleepTimerMode::EndOfTrack => SleepTimerEnd::EndOfTrack(player.playing_item_uri()),
+            SleepTimerMode::EndOfContext => {
+                SleepTimerEnd::EndOfContext(player.playing_context_id())
+            }
+        };
+        Self {
+            mode,
+            end,
+            fade_out,
+            volume_before_fade_out: None,
+        }
+    }
+
+    /// Get the remaining time of the timer, if it can be determined
+    pub fn remaining(&self, player: &PlayerState, data: &AppData) -> Option<Duration> {
+        let track_remaining = || {
+            let duration = match player.currently_playing()? {
+                rspotify::model::PlayableItem::Track(track) => track.duration,
+                rspotify::model::PlayableItem::Episode(episode) => episode.duration,
+            };
+            // the estimated progress can go past the track's duration before the next track is loaded
+            Some(
+                (duration - player.playback_progress()?)
+                    .to_std()
+                    .unwrap_or_default(),
+            )
+        };
+
+        match self.end {
+            SleepTimerEnd::Time(time) => Some(time.saturating_duration_since(Instant::now())),
+            SleepTimerEnd::EndOfTrack(ref uri) => {
+                if uri.is_some() && player.playing_item_uri() != *uri {
+                    // the track has ended and the playback has moved to another item (or stopped)
+                    return Some(Duration::ZERO);
+                }
+                track_remaining()
+            }
+            SleepTimerEnd::EndOfContext(ref id) => {
+                if player.playing_context_id() != *id {
+                    // the context has ended and the playback has moved to another context
+                    return Some(Duration::ZERO);
+                }
+
+                // the remaining time can only be determined when playing
+                // the context's last track in order
+                let playback = player.playback.as_ref()?;
+                let tracks = data.context_tracks(id.as_ref()?)?;
+                let playing_uri = match player.currently_playing()? {
+                    rspotify::model::PlayableItem::Track(track) => track.id.as_ref()?.uri(),
+                    rspotify::model::PlayableItem::Episode(_) => return None,
+                };
+                if playback.shuffle_state || tracks.last()?.id.uri() != playing_uri {
+                    return None;
+                }
+                track_remaining()
+            }
+        }
+    }
 }
 
 impl PlayerState {
@@ -68,6 +221,14 @@ impl PlayerState {
         }
     }
 
+    /// Get the URI of the currently playing item
+    pub fn playing_item_uri(&self) -> Option<String> {
+        match self.currently_playing()? {
+            rspotify::model::PlayableItem::Track(track) => Some(track.id.as_ref()?.uri()),
+            rspotify::model::PlayableItem::Episode(episode) => Some(episode.id.uri()),
+        }
+    }
+
     pub fn playing_context_id(&self) -> Option<ContextId> {
         match self.playback {
             Some(ref playback) => match playback.context {
@@ -9,7 +9,8 @@ pub type UIStateGuard<'a> = parking_lot::MutexGuard<'a, UIState>;
 mod page;
 mod popup;
 
-use super::TracksId;
+use super::{Track, TrackId, TracksId};
+use std::collections::HashSet;
 
 pub use page::*;
 pub use popup::*;
@@ -37,14 +38,118 @@ pub struct UIState {
     /// The rectangle representing the playback progress bar,
     /// which is mainly used to handle mouse click events (for seeking command)
     pub playback_progress_bar_rect: ratatui::layout::Rect,
+    /// The list/table windows of the current page rendered in the last frame,
+    /// which are used to handle mouse events
+    pub page_windows: Vec<(PageWindow, WindowArea)>,
+    /// The list popup rendered in the last frame (if any)
+    pub popup_window: Option<WindowArea>,
+    /// The time and position of the last left click, which is used to detect double clicks
+    pub last_mouse_click: Option<(std::time::Instant, ratatui::layout::Position)>,
 
     /// Count prefix for vim-style navigation (e.g., 5j, 10k)
     pub count_prefix: Option<usize>,
 
+    /// The marked tracks of the current page's track table, which are used for bulk actions
+    pub track_marks: TrackMarks,
+
     #[cfg(feature = "image")]
     pub last_cover_image_render_info: ImageRenderInfo,
 }
 
+/// The area of a rendered list/table window
+#[derive(Debug, Clone, Copy)]
+pub struct WindowArea {
+    /// the rectangle of the window's items, excluding the window's borders and table header
+    pub rect: Rect,
+    pub n_items: usize,
+}
+
+impl WindowArea {
+    pub fn list(rect: Rect, n_items: usize) -> Self {
+        Self { rect, n_items }
+    }
+
+    /// the area of a table window, whose first row is the table's header
+    pub fn table(rect: Rect, n_items: usize) -> Self {
+        Self {
+            rect: Rect {
+                y: rect.y.saturating_add(1),
+                height: rect.height.saturating_sub(1),
+                ..rect
+            },
+            n_items,
+        }
+    }
+
+    /// Get the id of the item at a position given the window's scroll offset
+    pub fn item_at(&self, position: ratatui::layout::Position, offset: usize) -> Option<usize> {
+        if !self.rect.contains(position) {
+            return None;
+        }
+        let id = offset + usize::from(position.y - self.rect.y);
+        (id < self.n_items).then_some(id)
+    }
+}
+
+/// The marked tracks of a track table
+#[derive(Default, Debug)]
+pub struct TrackMarks {
+    /// the IDs of the tracks marked one by one or by a committed visual selection
+    pub ids: HashSet<TrackId<'static>>,
+    /// the position of the track starting the visual selection,
+    /// which ranges from that track to the selected track
+    pub visual_anchor: Option<usize>,
+}
+
+impl TrackMarks {
+    /// Return whether there exists a marked track or an ongoing visual selection
+    pub fn is_active(&self) -> bool {
+        !self.ids.is_empty() || self.visual_anchor.is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+        self.visual_anchor = None;
+    }
+
+    /// Toggle the mark of a track
+    pub fn toggle(&mut self, id: &TrackId<'static>) {
+        if !self.ids.remove(id) {
+            self.ids.insert(id.clone());
+        }
+    }
+
+    /// Check if a track at position `pos` in the table is marked,
+    /// given the position of the table's selected track
+    pub fn is_marked(&self, track: &Track, pos: usize, selected: usize) -> bool {
+        self.ids.contains(&track.id)
+            || self
+                .visual_anchor
+                .is_some_and(|anchor| pos >= anchor.min(selected) && pos <= anchor.max(selected))
+    }
+
+    /// Get the marked tracks of a table in the table's order
+    pub fn marked_tracks<'a>(&self, tracks: &[&'a Track], selected: usize) -> Vec<&'a Track> {
+        tracks
+            .iter()
+            .enumerate()
+            .filter(|(pos, t)| self.is_marked(t, *pos, selected))
+            .map(|(_, t)| *t)
+            .collect()
+    }
+
+    /// Mark the tracks of the ongoing visual selection (if any) and end the selection
+    pub fn commit_visual_selection(&mut self, tracks: &[&Track], selected: usize) {
+        let ids = self
+            .marked_tracks(tracks, selected)
+            .into_iter()
+            .map(|t| t.id.clone())
+            .collect::<Vec<_>>();
+        self.ids.extend(ids);
+        self.visual_anchor = None;
+    }
+}
+
 impl UIState {
     pub fn current_page(&self) -> &PageState {
         self.history.last().expect("non-empty history")
@@ -64,6 +169,7 @@ impl UIState {
     pub fn new_page(&mut self, page: PageState) {
         self.history.push(page);
         self.popup = None;
+        self.track_marks.clear();
     }
 
     pub fn new_radio_page(&mut self, uri: &str) {
@@ -90,33 +196,36 @@ impl UIState {
     /// Get a list of items possibly filtered by a search query if exists a search popup
     pub fn search_filtered_items<'a, T: std::fmt::Display>(&self, items: &'a [T]) -> Vec<&'a T> {
         match self.popup {
-            Some(PopupState::Search { ref query }) => {
-                let query = query.to_lowercase();
-
-                #[cfg(feature = "fzf")]
-                return fuzzy_search_items(items, &query);
-
-                #[cfg(not(feature = "fzf"))]
-                items
-                    .iter()
-                    .filter(|t| {
-                        if query.is_empty() {
-                            true
-                        } else {
-                            let t = t.to_string().to_lowercase();
-                            query
-                                .split(' ')
-                                .filter(|q| !q.is_empty())
-                                .all(|q| t.contains(q))
-                        }
-                    })
-                    .collect::<Vec<_>>()
-            }
+            Some(PopupState::Search { ref query }) => filter_items(items, query),
             _ => items.iter().collect::<Vec<_>>(),
         }
     }
 }
 
+/// Get a list of items filtered by a search query
+pub fn filter_items<'a, T: std::fmt::Display>(items: &'a [T], query: &str) -> Vec<&'a T> {
+    let query = query.to_lowercase();
+
+    #[cfg(feature = "fzf")]
+    return fuzzy_search_items(items, &query);
+
+    #[cfg(not(feature = "fzf"))]
+    items
+        .iter()
+        .filter(|t| {
+            if query.is_empty() {
+                true
+            } else {
+                let t = t.to_string().to_lowercase();
+                query
+                    .split(' ')
+                    .filter(|q| !q.is_empty())
+                    .all(|q| t.contains(q))
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
 #[cfg(feature = "fzf")]
 use fuzzy_matcher::skim::SkimMatcherV2;
 use ratatui::layout::Rect;
@@ -157,9 +266,14 @@ impl Default for UIState {
             popup: None,
 
             playback_progress_bar_rect: Rect::default(),
+            page_windows: vec![],
+            popup_window: None,
+            last_mouse_click: None,
 
             count_prefix: None,
 
+            track_marks: TrackMarks::default(),
+
             #[cfg(feature = "image")]
             last_cover_image_render_info: ImageRenderInfo::default(),
         }
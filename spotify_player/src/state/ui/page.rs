@@ -23,6 +23,12 @@ pub enum PageState {
         track_uri: String,
         track: String,
         artists: String,
+        /// the line selected by the user, no selection means
+        /// the page follows the currently playing line
+        cursor: ListState,
+        /// the lyrics' scroll offset, which moves toward the offset centering
+        /// the selected (or playing) line at each render to scroll smoothly
+        scroll_offset: usize,
     },
     Browse {
         state: BrowsePageUIState,
@@ -33,6 +39,10 @@ pub enum PageState {
     CommandHelp {
         scroll_offset: usize,
     },
+    #[cfg(feature = "streaming")]
+    Visualizer {
+        mode: VisualizerMode,
+    },
 }
 
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -44,6 +54,8 @@ pub enum PageType {
     Lyrics,
     Queue,
     CommandHelp,
+    #[cfg(feature = "streaming")]
+    Visualizer,
 }
 
 #[derive(Clone, Debug)]
@@ -66,6 +78,13 @@ pub struct SearchPageUIState {
     pub focus: SearchFocusState,
 }
 
+#[cfg(feature = "streaming")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VisualizerMode {
+    Spectrum,
+    Waveform,
+}
+
 #[derive(Clone, Debug)]
 pub enum ContextPageType {
     CurrentPlaying,
@@ -94,6 +113,16 @@ pub enum ContextPageUIState {
     },
 }
 
+/// A list/table window of a page
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PageWindow {
+    /// the only list/table window of a page
+    Main,
+    Library(LibraryFocusState),
+    Search(SearchFocusState),
+    Artist(ArtistFocusState),
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum LibraryFocusState {
     Playlists,
@@ -147,6 +176,8 @@ impl PageState {
             PageState::Lyrics { .. } => PageType::Lyrics,
             PageState::Queue { .. } => PageType::Queue,
             PageState::CommandHelp { .. } => PageType::CommandHelp,
+            #[cfg(feature = "streaming")]
+            PageState::Visualizer { .. } => PageType::Visualizer,
         }
     }
 
@@ -157,12 +188,38 @@ impl PageState {
         }
     }
 
+    /// Clear the selection in the currently focused window of the page.
+    pub fn select_none(&mut self) {
+        if let Some(MutableWindowState::List(state)) = self.focus_window_state_mut() {
+            state.select(None);
+        }
+    }
+
     /// The selected item's position in the currently focused window of the page.
     pub fn selected(&mut self) -> Option<usize> {
         self.focus_window_state_mut()
             .map(|state| state.selected())?
     }
 
+    /// Focus a window of the page.
+    /// Return `false` if the page doesn't have the window.
+    pub fn focus_window(&mut self, window: PageWindow) -> bool {
+        match (self, window) {
+            (Self::Library { state }, PageWindow::Library(focus)) => state.focus = focus,
+            (Self::Search { state, .. }, PageWindow::Search(focus)) => state.focus = focus,
+            (
+                Self::Context {
+                    state: Some(ContextPageUIState::Artist { focus: current, .. }),
+                    ..
+                },
+                PageWindow::Artist(focus),
+            ) => *current = focus,
+            (_, PageWindow::Main) => {}
+            _ => return false,
+        }
+        true
+    }
+
     /// The currently focused window state of the page.
     pub fn focus_window_state_mut(&mut self) -> Option<MutableWindowState> {
         match self {
@@ -231,10 +288,12 @@ impl PageState {
                     Some(MutableWindowState::List(state))
                 }
             },
-            Self::Lyrics { .. } => None,
+            Self::Lyrics { cursor, .. } => Some(MutableWindowState::List(cursor)),
             Self::CommandHelp { scroll_offset } | Self::Queue { scroll_offset } => {
                 Some(MutableWindowState::Scroll(scroll_offset))
             }
+            #[cfg(feature = "streaming")]
+            Self::Visualizer { .. } => None,
         }
     }
 }
@@ -326,6 +385,15 @@ impl MutableWindowState<'_> {
         }
     }
 
+    /// The offset of the window's first rendered item
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::List(state) => state.offset(),
+            Self::Table(state) => state.offset(),
+            Self::Scroll(scroll_offset) => **scroll_offset,
+        }
+    }
+
     pub fn selected(&self) -> Option<usize> {
         match self {
             Self::List(state) => state.selected(),
@@ -22,7 +22,21 @@ pub enum PopupState {
     DeviceList(ListState),
     ArtistList(ArtistPopupAction, Vec<Artist>, ListState),
     ThemeList(Vec<crate::config::Theme>, ListState),
+    /// a list of DSP presets' names, `None` for disabling the DSP stage
+    #[cfg(feature = "streaming")]
+    DspPresetList(Vec<Option<String>>, ListState),
+    /// a list of sleep timer's modes, `None` for cancelling the sleep timer
+    SleepTimerList(Vec<Option<crate::state::SleepTimerMode>>, ListState),
     ActionList(Box<ActionListItem>, ListState),
+    /// a popup for searching and running commands and actions on the selected item
+    CommandPalette {
+        query: LineInput,
+        entries: Vec<CommandPaletteEntry>,
+        /// the selected item (if any) whose actions are included in the entries
+        item: Option<Box<ActionListItem>>,
+        list_state: ListState,
+    },
+    CommandLine(CommandLinePopupState),
     PlaylistCreate {
         name: LineInput,
         desc: LineInput,
@@ -33,6 +47,7 @@ pub enum PopupState {
 #[derive(Debug, Clone)]
 pub enum ActionListItem {
     Track(Track, Vec<command::Action>),
+    Tracks(Vec<Track>, Vec<command::Action>),
     Artist(Artist, Vec<command::Action>),
     Album(Album, Vec<command::Action>),
     Playlist(Playlist, Vec<command::Action>),
@@ -40,15 +55,39 @@ pub enum ActionListItem {
     Episode(Episode, Vec<command::Action>),
 }
 
+/// The state of the command line popup
+#[derive(Debug)]
+pub struct CommandLinePopupState {
+    pub input: LineInput,
+    /// the previously entered commands, from the oldest to the newest
+    pub history: Vec<String>,
+    /// the position of the history's command shown in the input (if any)
+    pub history_id: Option<usize>,
+    /// the completions of the input, computed when starting completing the input
+    pub completions: Vec<String>,
+    /// the position of the completion shown in the input
+    pub completion_id: usize,
+    /// the error of the last entered command (if any)
+    pub error: Option<String>,
+}
+
+/// An entry in the command palette
+#[derive(Debug, Clone)]
+pub struct CommandPaletteEntry {
+    pub command: command::CommandOrAction,
+    /// the entry's description, including its key bindings
+    pub desc: String,
+}
+
 /// An action on an item in a playlist popup list
 #[derive(Debug)]
 pub enum PlaylistPopupAction {
     Browse {
         folder_id: usize,
     },
-    AddTrack {
+    AddTracks {
         folder_id: usize,
-        track_id: TrackId<'static>,
+        track_ids: Vec<TrackId<'static>>,
     },
     AddEpisode {
         folder_id: usize,
@@ -73,8 +112,12 @@ impl PopupState {
             | Self::UserSavedAlbumList(list_state)
             | Self::ArtistList(.., list_state)
             | Self::ThemeList(.., list_state)
-            | Self::ActionList(.., list_state) => Some(list_state),
-            Self::Search { .. } | Self::PlaylistCreate { .. } => None,
+            | Self::SleepTimerList(.., list_state)
+            | Self::ActionList(.., list_state)
+            | Self::CommandPalette { list_state, .. } => Some(list_state),
+            #[cfg(feature = "streaming")]
+            Self::DspPresetList(.., list_state) => Some(list_state),
+            Self::Search { .. } | Self::CommandLine(_) | Self::PlaylistCreate { .. } => None,
         }
     }
 
@@ -87,8 +130,12 @@ impl PopupState {
             | Self::UserSavedAlbumList(list_state)
             | Self::ArtistList(.., list_state)
             | Self::ThemeList(.., list_state)
-            | Self::ActionList(.., list_state) => Some(list_state),
-            Self::Search { .. } | Self::PlaylistCreate { .. } => None,
+            | Self::SleepTimerList(.., list_state)
+            | Self::ActionList(.., list_state)
+            | Self::CommandPalette { list_state, .. } => Some(list_state),
+            #[cfg(feature = "streaming")]
+            Self::DspPresetList(.., list_state) => Some(list_state),
+            Self::Search { .. } | Self::CommandLine(_) | Self::PlaylistCreate { .. } => None,
         }
     }
 
@@ -109,39 +156,71 @@ impl PopupState {
     }
 }
 
+impl CommandLinePopupState {
+    pub fn new(history: Vec<String>) -> Self {
+        Self {
+            input: LineInput::default(),
+            history,
+            history_id: None,
+            completions: vec![],
+            completion_id: 0,
+            error: None,
+        }
+    }
+}
+
 impl ActionListItem {
     pub fn n_actions(&self) -> usize {
-        match self {
-            ActionListItem::Track(.., actions)
-            | ActionListItem::Artist(.., actions)
-            | ActionListItem::Album(.., actions)
-            | ActionListItem::Playlist(.., actions)
-            | ActionListItem::Show(.., actions)
-            | ActionListItem::Episode(.., actions) => actions.len(),
-        }
+        self.actions().len()
     }
 
-    pub fn name(&self) -> &str {
+    pub fn name(&self) -> String {
         match self {
-            ActionListItem::Track(track, ..) => &track.name,
-            ActionListItem::Artist(artist, ..) => &artist.name,
-            ActionListItem::Album(album, ..) => &album.name,
-            ActionListItem::Playlist(playlist, ..) => &playlist.name,
-            ActionListItem::Show(show, ..) => &show.name,
-            ActionListItem::Episode(episode, ..) => &episode.name,
+            ActionListItem::Track(track, ..) => track.name.clone(),
+            ActionListItem::Tracks(tracks, ..) => format!("{} tracks", tracks.len()),
+            ActionListItem::Artist(artist, ..) => artist.name.clone(),
+            ActionListItem::Album(album, ..) => album.name.clone(),
+            ActionListItem::Playlist(playlist, ..) => playlist.name.clone(),
+            ActionListItem::Show(show, ..) => show.name.clone(),
+            ActionListItem::Episode(episode, ..) => episode.name.clone(),
         }
     }
 
-    pub fn actions_desc(&self) -> Vec<String> {
+    pub fn actions(&self) -> &[command::Action] {
         match self {
             ActionListItem::Track(.., actions)
+            | ActionListItem::Tracks(.., actions)
             | ActionListItem::Artist(.., actions)
             | ActionListItem::Album(.., actions)
             | ActionListItem::Playlist(.., actions)
             | ActionListItem::Show(.., actions)
-            | ActionListItem::Episode(.., actions) => {
-                actions.iter().map(|a| format!("{a:?}")).collect::<Vec<_>>()
-            }
+            | ActionListItem::Episode(.., actions) => actions,
+        }
+    }
+
+    /// the context of the item to handle its actions
+    pub fn action_context(&self) -> command::ActionContext {
+        match self.clone() {
+            ActionListItem::Track(track, ..) => track.into(),
+            ActionListItem::Tracks(tracks, ..) => command::ActionContext::Tracks(tracks),
+            ActionListItem::Artist(artist, ..) => artist.into(),
+            ActionListItem::Album(album, ..) => album.into(),
+            ActionListItem::Playlist(playlist, ..) => playlist.into(),
+            ActionListItem::Show(show, ..) => show.into(),
+            ActionListItem::Episode(episode, ..) => episode.into(),
         }
     }
+
+    pub fn actions_desc(&self) -> Vec<String> {
+        self.actions()
+            .iter()
+            .map(|a| format!("{a:?}"))
+            .collect::<Vec<_>>()
+    }
+}
+
+impl std::fmt::Display for CommandPaletteEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.desc)
+    }
 }
@@ -4,55 +4,89 @@ use librespot_connect::{config::ConnectConfig, spirc::Spirc};
 use librespot_core::authentication::Credentials;
 use librespot_core::Session;
 use librespot_core::{config::DeviceType, spotify_id};
-use librespot_playback::mixer::MixerConfig;
+use librespot_playback::mixer::{Mixer, MixerConfig};
 use librespot_playback::{
     audio_backend,
-    config::{AudioFormat, Bitrate, PlayerConfig},
-    mixer::{self, Mixer},
-    player,
+    config::{
+        AudioFormat, Bitrate, NormalisationMethod, NormalisationType, PlayerConfig, VolumeCtrl,
+    },
+    mixer, player,
 };
 use rspotify::model::{EpisodeId, Id, PlayableId, TrackId};
 use serde::Serialize;
+use std::str::FromStr;
 use std::sync::Arc;
-
-#[cfg(not(any(
-    feature = "rodio-backend",
-    feature = "alsa-backend",
-    feature = "pulseaudio-backend",
-    feature = "portaudio-backend",
-    feature = "jackaudio-backend",
-    feature = "rodiojack-backend",
-    feature = "sdl-backend",
-    feature = "gstreamer-backend"
-)))]
-compile_error!("Streaming feature is enabled but no audio backend has been selected. Consider adding one of the following features:
-    rodio-backend,
-    alsa-backend,
-    pulseaudio-backend,
-    portaudio-backend,
-    jackaudio-backend,
-    rodiojack-backend,
-    sdl-backend,
-    gstreamer-backend
-For more information, visit https://github.com/aome510/spotify-player?tab=readme-ov-file#streaming
-");
+use std::time::Duration;
 
 #[derive(Debug, Serialize)]
+#[serde(tag = "type")]
 enum PlayerEvent {
     Changed {
+        #[serde(serialize_with = "serialize_uri")]
         playable_id: PlayableId<'static>,
     },
     Playing {
+        #[serde(serialize_with = "serialize_uri")]
         playable_id: PlayableId<'static>,
         position_ms: u32,
     },
     Paused {
+        #[serde(serialize_with = "serialize_uri")]
         playable_id: PlayableId<'static>,
         position_ms: u32,
     },
     EndOfTrack {
+        #[serde(serialize_with = "serialize_uri")]
+        playable_id: PlayableId<'static>,
+    },
+    Stopped {
+        #[serde(serialize_with = "serialize_uri")]
+        playable_id: PlayableId<'static>,
+    },
+    Loading {
+        #[serde(serialize_with = "serialize_uri")]
+        playable_id: PlayableId<'static>,
+        position_ms: u32,
+    },
+    Preloading {
+        #[serde(serialize_with = "serialize_uri")]
         playable_id: PlayableId<'static>,
     },
+    Unavailable {
+        #[serde(serialize_with = "serialize_uri")]
+        playable_id: PlayableId<'static>,
+    },
+    Seeked {
+        #[serde(serialize_with = "serialize_uri")]
+        playable_id: PlayableId<'static>,
+        position_ms: u32,
+    },
+    VolumeChanged {
+        /// volume in percentage (from 0 to 100)
+        volume: u8,
+    },
+    ShuffleChanged {
+        shuffle: bool,
+    },
+    RepeatChanged {
+        repeat: bool,
+    },
+    SessionConnected {
+        connection_id: String,
+        user_name: String,
+    },
+    SessionDisconnected {
+        connection_id: String,
+        user_name: String,
+    },
+    SessionClientChanged {
+        client_id: String,
+        client_name: String,
+    },
+}
+
+fn serialize_uri<S: serde::Serializer>(id: &PlayableId<'static>, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&id.uri())
 }
 
 impl PlayerEvent {
@@ -81,6 +115,64 @@ impl PlayerEvent {
             PlayerEvent::EndOfTrack { playable_id } => {
                 vec!["EndOfTrack".to_string(), playable_id.uri()]
             }
+            PlayerEvent::Stopped { playable_id } => {
+                vec!["Stopped".to_string(), playable_id.uri()]
+            }
+            PlayerEvent::Loading {
+                playable_id,
+                position_ms,
+            } => vec![
+                "Loading".to_string(),
+                playable_id.uri(),
+                position_ms.to_string(),
+            ],
+            PlayerEvent::Preloading { playable_id } => {
+                vec!["Preloading".to_string(), playable_id.uri()]
+            }
+            PlayerEvent::Unavailable { playable_id } => {
+                vec!["Unavailable".to_string(), playable_id.uri()]
+            }
+            PlayerEvent::Seeked {
+                playable_id,
+                position_ms,
+            } => vec![
+                "Seeked".to_string(),
+                playable_id.uri(),
+                position_ms.to_string(),
+            ],
+            PlayerEvent::VolumeChanged { volume } => {
+                vec!["VolumeChanged".to_string(), volume.to_string()]
+            }
+            PlayerEvent::ShuffleChanged { shuffle } => {
+                vec!["ShuffleChanged".to_string(), shuffle.to_string()]
+            }
+            PlayerEvent::RepeatChanged { repeat } => {
+                vec!["RepeatChanged".to_string(), repeat.to_string()]
+            }
+            PlayerEvent::SessionConnected {
+                connection_id,
+                user_name,
+            } => vec![
+                "SessionConnected".to_string(),
+                connection_id.clone(),
+                user_name.clone(),
+            ],
+            PlayerEvent::SessionDisconnected {
+                connection_id,
+                user_name,
+            } => vec![
+                "SessionDisconnected".to_string(),
+                connection_id.clone(),
+                user_name.clone(),
+            ],
+            PlayerEvent::SessionClientChanged {
+                client_id,
+                client_name,
+            } => vec![
+                "SessionClientChanged".to_string(),
+                client_id.clone(),
+                client_name.clone(),
+            ],
         }
     }
 }
@@ -99,6 +191,11 @@ fn spotify_id_to_playable_id(id: spotify_id::SpotifyId) -> anyhow::Result<Playab
     }
 }
 
+/// Convert a `librespot` volume (from 0 to 65535) into a percentage volume (from 0 to 100)
+fn volume_to_percent(volume: u16) -> u8 {
+    (f64::from(volume) / 65535.0 * 100.0).round() as u8
+}
+
 impl PlayerEvent {
     pub fn from_librespot_player_event(e: player::PlayerEvent) -> anyhow::Result<Option<Self>> {
         Ok(match e {
@@ -124,27 +221,247 @@ impl PlayerEvent {
             player::PlayerEvent::EndOfTrack { track_id, .. } => Some(PlayerEvent::EndOfTrack {
                 playable_id: spotify_id_to_playable_id(track_id)?,
             }),
+            player::PlayerEvent::Stopped { track_id, .. } => Some(PlayerEvent::Stopped {
+                playable_id: spotify_id_to_playable_id(track_id)?,
+            }),
+            player::PlayerEvent::Loading {
+                track_id,
+                position_ms,
+                ..
+            } => Some(PlayerEvent::Loading {
+                playable_id: spotify_id_to_playable_id(track_id)?,
+                position_ms,
+            }),
+            player::PlayerEvent::Preloading { track_id } => Some(PlayerEvent::Preloading {
+                playable_id: spotify_id_to_playable_id(track_id)?,
+            }),
+            player::PlayerEvent::Unavailable { track_id, .. } => Some(PlayerEvent::Unavailable {
+                playable_id: spotify_id_to_playable_id(track_id)?,
+            }),
+            player::PlayerEvent::Seeked {
+                track_id,
+                position_ms,
+                ..
+            } => Some(PlayerEvent::Seeked {
+                playable_id: spotify_id_to_playable_id(track_id)?,
+                position_ms,
+            }),
+            player::PlayerEvent::VolumeChanged { volume } => Some(PlayerEvent::VolumeChanged {
+                volume: volume_to_percent(volume),
+            }),
+            player::PlayerEvent::ShuffleChanged { shuffle } => {
+                Some(PlayerEvent::ShuffleChanged { shuffle })
+            }
+            player::PlayerEvent::RepeatChanged { repeat } => {
+                Some(PlayerEvent::RepeatChanged { repeat })
+            }
+            player::PlayerEvent::SessionConnected {
+                connection_id,
+                user_name,
+            } => Some(PlayerEvent::SessionConnected {
+                connection_id,
+                user_name,
+            }),
+            player::PlayerEvent::SessionDisconnected {
+                connection_id,
+                user_name,
+            } => Some(PlayerEvent::SessionDisconnected {
+                connection_id,
+                user_name,
+            }),
+            player::PlayerEvent::SessionClientChanged {
+                client_id,
+                client_name,
+                ..
+            } => Some(PlayerEvent::SessionClientChanged {
+                client_id,
+                client_name,
+            }),
             _ => None,
         })
     }
 }
 
-fn execute_player_event_hook_command(
-    cmd: &config::Command,
+/// A JSON document describing a player event and the current playback,
+/// passed to the player event hook command
+#[derive(Debug, Serialize)]
+struct PlayerEventPayload<'a> {
+    event: &'a PlayerEvent,
+    item: Option<PlayerEventItem>,
+    position_ms: Option<i64>,
+    is_playing: Option<bool>,
+    shuffle: Option<bool>,
+    repeat: Option<rspotify::model::RepeatState>,
+    device: Option<PlayerEventDevice>,
+    context_uri: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlayerEventItem {
+    id: String,
+    name: String,
+    artists: Vec<String>,
+    album: String,
+    duration_ms: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct PlayerEventDevice {
+    id: Option<String>,
+    name: String,
+    volume: Option<u32>,
+}
+
+impl<'a> PlayerEventPayload<'a> {
+    fn new(event: &'a PlayerEvent, state: &SharedState) -> Self {
+        let playback = state.player.read().current_playback();
+        let item = playback
+            .as_ref()
+            .and_then(|p| p.item.as_ref())
+            .and_then(|item| match item {
+                rspotify::model::PlayableItem::Track(track) => Some(PlayerEventItem {
+                    id: track.id.as_ref()?.uri(),
+                    name: track.name.clone(),
+                    artists: track.artists.iter().map(|a| a.name.clone()).collect(),
+                    album: track.album.name.clone(),
+                    duration_ms: track.duration.num_milliseconds(),
+                }),
+                rspotify::model::PlayableItem::Episode(episode) => Some(PlayerEventItem {
+                    id: episode.id.uri(),
+                    name: episode.name.clone(),
+                    artists: vec![episode.show.publisher.clone()],
+                    album: episode.show.name.clone(),
+                    duration_ms: episode.duration.num_milliseconds(),
+                }),
+            });
+
+        Self {
+            event,
+            item,
+            position_ms: playback
+                .as_ref()
+                .and_then(|p| p.progress)
+                .map(|p| p.num_milliseconds()),
+            is_playing: playback.as_ref().map(|p| p.is_playing),
+            shuffle: playback.as_ref().map(|p| p.shuffle_state),
+            repeat: playback.as_ref().map(|p| p.repeat_state),
+            device: playback.as_ref().map(|p| PlayerEventDevice {
+                id: p.device.id.clone(),
+                name: p.device.name.clone(),
+                volume: p.device.volume_percent,
+            }),
+            context_uri: playback.and_then(|p| p.context.map(|c| c.uri)),
+        }
+    }
+}
+
+async fn execute_player_event_hook_command(
+    cmd: &'static config::Command,
     event: &PlayerEvent,
+    state: &SharedState,
 ) -> anyhow::Result<()> {
-    cmd.execute(Some(event.args()))?;
+    let args = event.args();
+    let payload = serde_json::to_string(&PlayerEventPayload::new(event, state))?;
+    // the command is waited in a blocking task to not block the player event task's thread
+    tokio::task::spawn_blocking(move || {
+        cmd.execute_with_envs(
+            Some(args),
+            &[("SPOTIFY_PLAYER_EVENT_JSON", payload.as_str())],
+        )
+    })
+    .await??;
 
     Ok(())
 }
 
+/// Check that an ALSA mixer's device and control exist
+#[cfg(feature = "alsa-backend")]
+fn check_alsa_mixer(device: &str, control: &str) -> anyhow::Result<()> {
+    let mixer = alsa::mixer::Mixer::new(device, false).with_context(|| {
+        format!("invalid `mixer_device` config: failed to open the ALSA mixer device {device}")
+    })?;
+    anyhow::ensure!(
+        mixer
+            .find_selem(&alsa::mixer::SelemId::new(control, 0))
+            .is_some(),
+        "invalid `mixer_control` config: the ALSA mixer device {device} has no control {control}"
+    );
+    Ok(())
+}
+
+/// Check if an audio backend writes raw PCM audio to a file, stdout or a command
+/// instead of playing it on an audio device
+fn is_raw_pcm_backend(name: &str) -> bool {
+    matches!(name, "pipe" | "subprocess")
+}
+
+/// Check if `librespot` can list an audio backend's output devices
+fn supports_device_listing(name: &str) -> bool {
+    matches!(name, "alsa" | "rodio" | "portaudio")
+}
+
+/// Get a function creating an audio sink based on the device's backend configurations.
+///
+/// The sink processes audio samples using the active DSP preset before writing them to the backend.
+pub fn sink_builder(
+    state: &SharedState,
+) -> anyhow::Result<impl Fn() -> Box<dyn audio_backend::Sink> + Send + 'static> {
+    let device = &config::get_config().app_config.device;
+
+    let audio_format = device
+        .audio_format
+        .parse::<AudioFormat>()
+        .map_err(|()| anyhow::anyhow!("invalid audio format: {}", device.audio_format))?;
+    let output_device = device.output_device.clone();
+
+    let backend_name = match device.backend {
+        Some(ref name) => name.as_str(),
+        // the first compiled audio backend is the default one
+        None => match audio_backend::BACKENDS.first() {
+            Some((name, _)) if !is_raw_pcm_backend(name) => name,
+            _ => anyhow::bail!(
+                "no audio backend feature has been compiled, please set the device's `backend` to `pipe` or `subprocess`, \
+                 or build the application with an audio backend feature (e.g. `rodio-backend`)"
+            ),
+        },
+    };
+    let backend = audio_backend::find(Some(backend_name.to_string()))
+        .ok_or_else(|| anyhow::anyhow!("unknown audio backend: {backend_name}"))?;
+    if is_raw_pcm_backend(backend_name) {
+        match output_device {
+            None if backend_name == "subprocess" => {
+                anyhow::bail!("the `subprocess` audio backend requires the device's `output_device` to be a command")
+            }
+            // writing audio to stdout would break the application's UI
+            None if !state.is_daemon => {
+                anyhow::bail!("the `pipe` audio backend requires the device's `output_device` to be a file unless running as a daemon")
+            }
+            _ => {}
+        }
+        tracing::info!(
+            "Writing raw PCM audio ({} Hz, {} channels, {audio_format:?} samples) to {} using the {backend_name} audio backend",
+            librespot_playback::SAMPLE_RATE,
+            librespot_playback::NUM_CHANNELS,
+            output_device.as_deref().unwrap_or("stdout"),
+        );
+    }
+
+    let state = state.clone();
+    Ok(move || -> Box<dyn audio_backend::Sink> {
+        Box::new(crate::dsp::DspSink::new(
+            backend(output_device.clone(), audio_format),
+            state.clone(),
+        ))
+    })
+}
+
 /// Create a new streaming connection
 pub async fn new_connection(
     client: Client,
     state: SharedState,
     session: Session,
     creds: Credentials,
-) -> anyhow::Result<Spirc> {
+) -> anyhow::Result<(Spirc, Arc<dyn Mixer>)> {
     let configs = config::get_config();
     let device = &configs.app_config.device;
 
@@ -153,30 +470,74 @@ pub async fn new_connection(
     // So we need to convert from one format to another
     let volume = (f64::from(std::cmp::min(device.volume, 100_u8)) / 100.0 * 65535.0).round() as u16;
 
+    let volume_ctrl = VolumeCtrl::from_str(&device.volume_ctrl)
+        .map_err(|()| anyhow::anyhow!("invalid volume control: {}", device.volume_ctrl))?;
+
     let connect_config = ConnectConfig {
         name: device.name.clone(),
         device_type: device.device_type.parse::<DeviceType>().unwrap_or_default(),
         initial_volume: Some(volume),
+        // a device with a fixed volume doesn't allow volume changes
+        has_volume_ctrl: !matches!(volume_ctrl, VolumeCtrl::Fixed),
 
         // non-configurable fields, use default values.
         // We may allow users to configure these fields in a future release
-        has_volume_ctrl: true,
         is_group: false,
     };
 
     tracing::info!("Application's connect configurations: {:?}", connect_config);
 
-    let mixer = Arc::new(mixer::softmixer::SoftMixer::open(MixerConfig::default()));
+    let mixer_config = MixerConfig {
+        device: device.mixer_device.clone(),
+        control: device.mixer_control.clone(),
+        index: 0,
+        volume_ctrl,
+    };
+    // `librespot` panics when opening an ALSA mixer with an invalid device or control
+    #[cfg(feature = "alsa-backend")]
+    if device.mixer == "alsa" {
+        check_alsa_mixer(&device.mixer_device, &device.mixer_control)?;
+    }
+    let mixer = mixer::find(Some(&device.mixer))
+        .ok_or_else(|| anyhow::anyhow!("unknown mixer: {}", device.mixer))?(
+        mixer_config
+    );
     mixer.set_volume(volume);
 
-    let backend = audio_backend::find(None).expect("should be able to find an audio backend");
+    let new_sink = sink_builder(&state)?;
+
     let player_config = PlayerConfig {
         bitrate: device
             .bitrate
             .to_string()
             .parse::<Bitrate>()
             .unwrap_or_default(),
+        gapless: device.gapless,
         normalisation: device.normalization,
+        normalisation_type: device
+            .normalization_type
+            .parse::<NormalisationType>()
+            .map_err(|()| {
+                anyhow::anyhow!("invalid normalization type: {}", device.normalization_type)
+            })?,
+        normalisation_method: device
+            .normalization_method
+            .parse::<NormalisationMethod>()
+            .map_err(|()| {
+                anyhow::anyhow!(
+                    "invalid normalization method: {}",
+                    device.normalization_method
+                )
+            })?,
+        normalisation_pregain_db: device.normalization_pregain_db,
+        normalisation_threshold_dbfs: device.normalization_threshold_dbfs,
+        normalisation_attack_cf: player::duration_to_coefficient(Duration::from_millis(
+            device.normalization_attack_ms,
+        )),
+        normalisation_release_cf: player::duration_to_coefficient(Duration::from_millis(
+            device.normalization_release_ms,
+        )),
+        normalisation_knee_db: device.normalization_knee_db,
         ..Default::default()
     };
 
@@ -189,7 +550,7 @@ pub async fn new_connection(
         player_config,
         session.clone(),
         mixer.get_soft_volume(),
-        move || backend(None, AudioFormat::default()),
+        new_sink,
     );
 
     let player_event_task = tokio::task::spawn({
@@ -203,25 +564,90 @@ pub async fn new_connection(
                     Ok(Some(event)) => {
                         tracing::info!("Got a new player event: {event:?}");
                         match event {
-                            PlayerEvent::Playing { .. } => {
+                            PlayerEvent::Playing {
+                                ref playable_id,
+                                position_ms,
+                            } => {
+                                update_playback_progress(
+                                    &state,
+                                    playable_id,
+                                    position_ms,
+                                    Some(true),
+                                );
                                 let mut player = state.player.write();
                                 if let Some(playback) = player.buffered_playback.as_mut() {
                                     playback.is_playing = true;
                                 }
                             }
-                            PlayerEvent::Paused { .. } => {
+                            PlayerEvent::Paused {
+                                ref playable_id,
+                                position_ms,
+                            } => {
+                                update_playback_progress(
+                                    &state,
+                                    playable_id,
+                                    position_ms,
+                                    Some(false),
+                                );
                                 let mut player = state.player.write();
                                 if let Some(playback) = player.buffered_playback.as_mut() {
                                     playback.is_playing = false;
                                 }
                             }
+                            PlayerEvent::Seeked {
+                                ref playable_id,
+                                position_ms,
+                            } => {
+                                update_playback_progress(&state, playable_id, position_ms, None);
+                            }
+                            PlayerEvent::VolumeChanged { volume } => {
+                                let mut player = state.player.write();
+                                if let Some(playback) = player.buffered_playback.as_mut() {
+                                    playback.volume = Some(u32::from(volume));
+                                }
+                            }
+                            PlayerEvent::ShuffleChanged { shuffle } => {
+                                let mut player = state.player.write();
+                                if let Some(playback) = player.buffered_playback.as_mut() {
+                                    playback.shuffle_state = shuffle;
+                                }
+                            }
+                            PlayerEvent::Changed { .. } => {
+                                // the previous track's audio file may have been added to the audio cache
+                                crate::client::prune_audio_cache(&state);
+                            }
+                            PlayerEvent::EndOfTrack { ref playable_id } => {
+                                // other player state events are derived from the player's state changes below
+                                state
+                                    .events
+                                    .publish(crate::state::PlayerStateEvent::EndOfTrack {
+                                        id: playable_id.uri(),
+                                    });
+                            }
                             _ => {}
                         }
-                        client.update_playback(&state);
+                        state.publish_player_state_changes(matches!(
+                            event,
+                            PlayerEvent::Seeked { .. }
+                        ));
+                        // other events (e.g. volume or shuffle changes) are already
+                        // reflected in the buffered playback
+                        if matches!(
+                            event,
+                            PlayerEvent::Changed { .. }
+                                | PlayerEvent::Playing { .. }
+                                | PlayerEvent::Paused { .. }
+                                | PlayerEvent::EndOfTrack { .. }
+                                | PlayerEvent::Stopped { .. }
+                        ) {
+                            client.update_playback(&state);
+                        }
 
                         // execute a player event hook command
                         if let Some(ref cmd) = configs.app_config.player_event_hook_command {
-                            if let Err(err) = execute_player_event_hook_command(cmd, &event) {
+                            if let Err(err) =
+                                execute_player_event_hook_command(cmd, &event, &state).await
+                            {
                                 tracing::warn!(
                                     "Failed to execute player event hook command: {err:#}"
                                 );
@@ -236,7 +662,7 @@ pub async fn new_connection(
 
     tracing::info!("Starting an integrated Spotify player using librespot's spirc protocol");
 
-    let (spirc, spirc_task) = Spirc::new(connect_config, session, creds, player, mixer)
+    let (spirc, spirc_task) = Spirc::new(connect_config, session, creds, player, mixer.clone())
         .await
         .context("initialize spirc")?;
 
@@ -249,5 +675,73 @@ pub async fn new_connection(
 
     tracing::info!("New streaming connection has been established!");
 
-    Ok(spirc)
+    Ok((spirc, mixer))
+}
+
+/// Update the progress of the playback with the position reported by the integrated
+/// player, if the player's item is the currently playing item
+fn update_playback_progress(
+    state: &SharedState,
+    playable_id: &PlayableId<'static>,
+    position_ms: u32,
+    is_playing: Option<bool>,
+) {
+    let mut player = state.player.write();
+    let is_current = player
+        .currently_playing()
+        .and_then(rspotify::model::PlayableItem::id)
+        .is_some_and(|id| id.uri() == playable_id.uri());
+    if !is_current {
+        return;
+    }
+    if let Some(playback) = player.playback.as_mut() {
+        playback.progress = Some(chrono::TimeDelta::milliseconds(i64::from(position_ms)));
+        if let Some(is_playing) = is_playing {
+            playback.is_playing = is_playing;
+        }
+        player.playback_last_updated_time = Some(std::time::Instant::now());
+    }
+}
+
+/// Print the output devices that each compiled audio backend can see
+pub fn list_audio_devices(backend: Option<&str>) -> anyhow::Result<()> {
+    if let Some(name) = backend {
+        let backend = audio_backend::find(Some(name.to_string()))
+            .ok_or_else(|| anyhow::anyhow!("unknown audio backend: {name}"))?;
+        if !supports_device_listing(name) {
+            println!("device listing not supported for {name}");
+            return Ok(());
+        }
+        // `librespot` prints the backend's devices and exits the process
+        // when opening a sink with the `?` device
+        backend(Some("?".to_string()), AudioFormat::default());
+        return Ok(());
+    }
+
+    // Listing a backend's devices exits the process, so each backend is listed
+    // in a separate child process. Raw PCM backends don't have any device.
+    let exe = std::env::current_exe().context("get the application's executable")?;
+    let backends = audio_backend::BACKENDS
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| !is_raw_pcm_backend(name))
+        .collect::<Vec<_>>();
+    if backends.is_empty() {
+        println!("No audio backend with output devices has been compiled");
+    }
+    for name in backends {
+        println!("Backend: {name}");
+        if !supports_device_listing(name) {
+            println!("device listing not supported for {name}\n");
+            continue;
+        }
+        let status = std::process::Command::new(&exe)
+            .args(["audio-devices", "--backend", name])
+            .status()?;
+        if !status.success() {
+            eprintln!("Failed to list devices of the {name} backend");
+        }
+        println!();
+    }
+    Ok(())
 }
@@ -2,9 +2,10 @@ use crate::{
     config,
     state::{
         Album, Artist, ArtistFocusState, BrowsePageUIState, Context, ContextPageUIState,
-        DataReadGuard, Id, LibraryFocusState, MutableWindowState, PageState, PageType,
-        PlaybackMetadata, PlaylistCreateCurrentField, PlaylistFolderItem, PlaylistPopupAction,
-        PopupState, SearchFocusState, SharedState, Track, UIStateGuard,
+        DataReadGuard, Id, LibraryFocusState, MutableWindowState, PageState, PageType, PageWindow,
+        PlaybackMetadata, PlayerState, PlaylistCreateCurrentField, PlaylistFolderItem,
+        PlaylistPopupAction, PopupState, SearchFocusState, SharedState, Track, UIStateGuard,
+        WindowArea,
     },
 };
 use anyhow::{Context as AnyhowContext, Result};
@@ -104,6 +105,10 @@ fn clean_up(mut terminal: Terminal) -> Result<()> {
 fn render_application(frame: &mut Frame, state: &SharedState, ui: &mut UIStateGuard, rect: Rect) {
     // rendering order: playback window -> shortcut help popup -> other popups -> main layout
 
+    // the windows' areas are recorded again while rendering this frame
+    ui.page_windows.clear();
+    ui.popup_window = None;
+
     // render playback window before other popups and windows to ensure nothing is rendered on top
     // of the playback window, which is to avoid "duplicated images" issue
     // See: https://github.com/aome510/spotify-player/issues/498
@@ -133,6 +138,8 @@ fn render_main_layout(
         PageType::Lyrics => page::render_lyrics_page(is_active, frame, state, ui, rect),
         PageType::Queue => page::render_queue_page(frame, state, ui, rect),
         PageType::CommandHelp => page::render_commands_help_page(frame, ui, rect),
+        #[cfg(feature = "streaming")]
+        PageType::Visualizer => page::render_visualizer_page(frame, state, ui, rect),
     }
 }
 
@@ -6,9 +6,9 @@ use std::{
 use super::{
     config, utils, utils::construct_and_render_block, Album, Artist, ArtistFocusState, Borders,
     BrowsePageUIState, Cell, Constraint, Context, ContextPageUIState, DataReadGuard, Frame, Id,
-    Layout, LibraryFocusState, MutableWindowState, Orientation, PageState, Paragraph,
+    Layout, LibraryFocusState, MutableWindowState, Orientation, PageState, PageWindow, Paragraph,
     PlaylistFolderItem, Rect, Row, SearchFocusState, SharedState, Style, Table, Track,
-    UIStateGuard,
+    UIStateGuard, WindowArea,
 };
 use crate::state::BidiDisplay;
 use crate::ui::utils::to_bidi_string;
@@ -21,6 +21,10 @@ const COMMAND_TABLE_CONSTRAINTS: [Constraint; 3] = [
     Constraint::Percentage(50),
 ];
 
+/// the lyrics page scrolls by `1 / LYRICS_SCROLL_SMOOTHNESS` of the remaining
+/// distance to its target scroll offset at each render
+const LYRICS_SCROLL_SMOOTHNESS: usize = 4;
+
 // UI codes to render a page.
 // A `render_*_page` function should follow (not strictly) the below steps
 // 1. get data from the application's states
@@ -247,6 +251,33 @@ pub fn render_search_page(
         n_episodes,
         &mut page_state.episode_list,
     );
+
+    ui.page_windows.extend([
+        (
+            PageWindow::Search(SearchFocusState::Tracks),
+            WindowArea::list(track_rect, n_tracks),
+        ),
+        (
+            PageWindow::Search(SearchFocusState::Albums),
+            WindowArea::list(album_rect, n_albums),
+        ),
+        (
+            PageWindow::Search(SearchFocusState::Artists),
+            WindowArea::list(artist_rect, n_artists),
+        ),
+        (
+            PageWindow::Search(SearchFocusState::Playlists),
+            WindowArea::list(playlist_rect, n_playlists),
+        ),
+        (
+            PageWindow::Search(SearchFocusState::Shows),
+            WindowArea::list(show_rect, n_shows),
+        ),
+        (
+            PageWindow::Search(SearchFocusState::Episodes),
+            WindowArea::list(episode_rect, n_episodes),
+        ),
+    ]);
 }
 
 pub fn render_context_page(
@@ -293,7 +324,7 @@ pub fn render_context_page(
             // render context description
             let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(0)]).split(rect);
 
-            let description = if let Context::Playlist { playlist, .. } = context {
+            let mut description = if let Context::Playlist { playlist, .. } = context {
                 format!(
                     "{} | {}",
                     context.description(),
@@ -306,6 +337,9 @@ pub fn render_context_page(
             } else {
                 context.description()
             };
+            if let Some(pinned) = data.user_data.pinned_contexts.get(&id.uri()) {
+                description = format!("{description} | {}", pinned.progress());
+            }
 
             frame.render_widget(
                 Paragraph::new(description).style(ui.theme.page_desc()),
@@ -508,6 +542,21 @@ pub fn render_library_page(
         n_artists,
         &mut page_state.followed_artist_list,
     );
+
+    ui.page_windows.extend([
+        (
+            PageWindow::Library(LibraryFocusState::Playlists),
+            WindowArea::list(playlist_rect, n_playlists),
+        ),
+        (
+            PageWindow::Library(LibraryFocusState::SavedAlbums),
+            WindowArea::list(album_rect, n_albums),
+        ),
+        (
+            PageWindow::Library(LibraryFocusState::FollowedArtists),
+            WindowArea::list(artist_rect, n_artists),
+        ),
+    ]);
 }
 
 pub fn render_browse_page(
@@ -564,10 +613,12 @@ pub fn render_browse_page(
         return;
     };
     utils::render_list_window(frame, list, rect, len, list_state);
+    ui.page_windows
+        .push((PageWindow::Main, WindowArea::list(rect, len)));
 }
 
 pub fn render_lyrics_page(
-    _is_active: bool,
+    is_active: bool,
     frame: &mut Frame,
     state: &SharedState,
     ui: &mut UIStateGuard,
@@ -590,10 +641,13 @@ pub fn render_lyrics_page(
         track_uri,
         track,
         artists,
-    } = ui.current_page_mut()
+        cursor,
+        scroll_offset,
+    } = ui.current_page()
     else {
         return;
     };
+    let prev_scroll_offset = *scroll_offset;
 
     let lyrics = match data.caches.lyrics.get(track_uri) {
         None => {
@@ -606,45 +660,236 @@ pub fn render_lyrics_page(
         }
         Some(Some(lyrics)) => lyrics,
     };
+    let offset = data.user_data.lyrics_offset(track_uri);
+    let progress = progress + offset;
+    // the line selected by the user (if any)
+    let selected = cursor
+        .selected()
+        .map(|id| std::cmp::min(id, lyrics.lines.len().saturating_sub(1)));
 
     // 4. Render the page's widgets
     // render lyric page description text
     let bidi_track = to_bidi_string(track);
     let bidi_artists = to_bidi_string(artists);
+    let offset_desc = if offset.is_zero() {
+        String::new()
+    } else {
+        format!(
+            ", offset {:+.1}s",
+            offset.num_milliseconds() as f64 / 1000.0
+        )
+    };
     frame.render_widget(
-        Paragraph::new(format!("{bidi_track} by {bidi_artists}")).style(ui.theme.page_desc()),
+        Paragraph::new(format!(
+            "{bidi_track} by {bidi_artists} (lyrics from {}{offset_desc})",
+            lyrics.provider
+        ))
+        .style(ui.theme.page_desc()),
         chunks[0],
     );
 
     // render lyric text
+    let half_height = (chunks[1].height / 2) as usize;
 
-    // the last played line id (1-based)
-    // zero value indicates no line has been played yet
-    let mut last_played_line_id = 0;
-    for (id, (t, _)) in lyrics.lines.iter().enumerate() {
-        if *t <= progress {
-            last_played_line_id = id + 1;
+    // the currently playing line id (if any)
+    let playing_line_id = if lyrics.synced {
+        lyrics.current_line_id(progress)
+    } else {
+        // unsynced lyrics don't have line timestamps, so instead of highlighting
+        // the currently playing line, scroll the lyrics proportionally to the playback's progress
+        let duration = match state.player.read().currently_playing() {
+            Some(rspotify::model::PlayableItem::Track(track)) => track.duration,
+            Some(rspotify::model::PlayableItem::Episode(episode)) => episode.duration,
+            None => chrono::Duration::zero(),
+        };
+        if duration > chrono::Duration::zero() {
+            let ratio = progress.num_milliseconds() as f64 / duration.num_milliseconds() as f64;
+            Some((ratio.clamp(0.0, 1.0) * lyrics.lines.len() as f64) as usize)
+        } else {
+            None
         }
-    }
+    };
+
+    let line_style = |id: usize| {
+        let mut style = match playing_line_id {
+            Some(playing_id) if lyrics.synced => match id.cmp(&playing_id) {
+                std::cmp::Ordering::Less => ui.theme.lyrics_played(),
+                std::cmp::Ordering::Equal => ui.theme.lyrics_playing(),
+                std::cmp::Ordering::Greater => Style::default(),
+            },
+            _ => Style::default(),
+        };
+        if Some(id) == selected {
+            style = style.patch(ui.theme.selection(is_active));
+        }
+        style
+    };
+    // keep the selected line (or the currently playing line if no line is selected)
+    // in the center if the line goes pass the upper half of lyrics section
+    let target_scroll_offset = selected
+        .or(playing_line_id)
+        .and_then(|id| id.checked_sub(half_height))
+        .unwrap_or_default();
+    // move toward the target offset by a fraction of the remaining distance
+    let distance = target_scroll_offset.abs_diff(prev_scroll_offset);
+    let step = distance.div_ceil(LYRICS_SCROLL_SMOOTHNESS);
+    let scroll_offset = if target_scroll_offset > prev_scroll_offset {
+        prev_scroll_offset + step
+    } else {
+        prev_scroll_offset - step
+    };
+
     let lines = lyrics
         .lines
         .iter()
         .enumerate()
-        .map(|(id, (_, line))| match (id + 1).cmp(&last_played_line_id) {
-            std::cmp::Ordering::Less => Line::styled(line, ui.theme.lyrics_played()),
-            std::cmp::Ordering::Equal => Line::styled(line, ui.theme.lyrics_playing()),
-            std::cmp::Ordering::Greater => Line::raw(line),
-        })
+        .map(|(id, (_, line))| Line::styled(to_bidi_string(line), line_style(id)))
         .collect::<Vec<_>>();
 
-    let mut paragraph = Paragraph::new(lines);
-    // keep the currently playing line in the center if
-    // the line goes pass the lower half of lyrics section
-    let half_height = (chunks[1].height / 2) as usize;
-    if let Some(offset) = last_played_line_id.checked_sub(half_height) {
-        paragraph = paragraph.scroll((offset as u16, 0));
+    match &lyrics.translations {
+        None => {
+            frame.render_widget(
+                Paragraph::new(lines).scroll((scroll_offset as u16, 0)),
+                chunks[1],
+            );
+        }
+        Some(translations) => {
+            // render the translations in a second column, sharing the same
+            // styles and scroll offset as the lyrics to keep the lines aligned
+            let translated_lines = translations
+                .iter()
+                .enumerate()
+                .map(|(id, line)| Line::styled(to_bidi_string(line), line_style(id)))
+                .collect::<Vec<_>>();
+            let columns = Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
+                .spacing(2)
+                .split(chunks[1]);
+            frame.render_widget(
+                Paragraph::new(lines).scroll((scroll_offset as u16, 0)),
+                columns[0],
+            );
+            frame.render_widget(
+                Paragraph::new(translated_lines).scroll((scroll_offset as u16, 0)),
+                columns[1],
+            );
+        }
+    }
+
+    drop(data);
+    if let PageState::Lyrics {
+        scroll_offset: offset,
+        ..
+    } = ui.current_page_mut()
+    {
+        *offset = scroll_offset;
+    }
+}
+
+#[cfg(feature = "streaming")]
+pub fn render_visualizer_page(
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    rect: Rect,
+) {
+    use crate::state::VisualizerMode;
+    use ratatui::{
+        symbols::Marker,
+        widgets::{Axis, Bar, BarChart, BarGroup, Chart, Dataset, GraphType, Wrap},
+    };
+
+    // 1. Get data
+    let PageState::Visualizer { mode } = ui.current_page() else {
+        return;
+    };
+    let mode = *mode;
+    let samples = {
+        let player = state.player.read();
+        let Some(playback) = player.buffered_playback.as_ref() else {
+            let rect =
+                construct_and_render_block("Visualizer", &ui.theme, Borders::ALL, frame, rect);
+            frame.render_widget(Paragraph::new("No playback available"), rect);
+            return;
+        };
+
+        // audio samples are only available when the integrated player is the active device
+        let device_name = &config::get_config().app_config.device.name;
+        if playback.device_name != *device_name {
+            let rect =
+                construct_and_render_block("Visualizer", &ui.theme, Borders::ALL, frame, rect);
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "The visualizer is only available when playing on the integrated device ({device_name}). \
+                     The audio of the current device ({}) can't be processed by the application.",
+                    playback.device_name
+                ))
+                .wrap(Wrap { trim: true }),
+                rect,
+            );
+            return;
+        }
+
+        if playback.is_playing {
+            state
+                .audio_samples
+                .lock()
+                .iter()
+                .copied()
+                .collect::<Vec<_>>()
+        } else {
+            vec![]
+        }
+    };
+
+    // 2. Construct the page's layout
+    let title = match mode {
+        VisualizerMode::Spectrum => "Visualizer (spectrum)",
+        VisualizerMode::Waveform => "Visualizer (waveform)",
+    };
+    let rect = construct_and_render_block(title, &ui.theme, Borders::ALL, frame, rect);
+
+    // 3. Construct and render the page's widget
+    match mode {
+        VisualizerMode::Spectrum => {
+            let n_bars = (rect.width / 3).clamp(1, 64);
+            let bars = crate::dsp::spectrum(&samples, usize::from(n_bars))
+                .into_iter()
+                .map(|level| {
+                    Bar::default()
+                        .value((level * 100.0) as u64)
+                        .text_value(String::new())
+                })
+                .collect::<Vec<_>>();
+            let chart = BarChart::default()
+                .data(BarGroup::default().bars(&bars))
+                .bar_width(((rect.width + 1) / n_bars).saturating_sub(1).max(1))
+                .bar_gap(1)
+                .bar_style(ui.theme.visualizer_spectrum())
+                .max(100);
+            frame.render_widget(chart, rect);
+        }
+        VisualizerMode::Waveform => {
+            // each cell of a braille canvas has two horizontal dots
+            let n_points = usize::from(rect.width) * 2;
+            let step = (samples.len() / n_points.max(1)).max(1);
+            let points = samples
+                .iter()
+                .step_by(step)
+                .take(n_points)
+                .enumerate()
+                .map(|(i, sample)| (i as f64, *sample))
+                .collect::<Vec<_>>();
+            let dataset = Dataset::default()
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(ui.theme.visualizer_waveform())
+                .data(&points);
+            let chart = Chart::new(vec![dataset])
+                .x_axis(Axis::default().bounds([0.0, n_points as f64]))
+                .y_axis(Axis::default().bounds([-1.0, 1.0]));
+            frame.render_widget(chart, rect);
+        }
     }
-    frame.render_widget(paragraph, chunks[1]);
 }
 
 pub fn render_commands_help_page(frame: &mut Frame, ui: &mut UIStateGuard, rect: Rect) {
@@ -932,6 +1177,17 @@ fn render_artist_context_page_windows(
         n_artists,
         related_artist_list,
     );
+
+    ui.page_windows.extend([
+        (
+            PageWindow::Artist(ArtistFocusState::Albums),
+            WindowArea::table(albums_rect, n_albums),
+        ),
+        (
+            PageWindow::Artist(ArtistFocusState::RelatedArtists),
+            WindowArea::list(related_artists_rect, n_artists),
+        ),
+    ]);
 }
 
 fn render_track_table(
@@ -963,16 +1219,22 @@ fn render_track_table(
         }
     }
 
+    let selected = ui.current_page_mut().selected().unwrap_or_default();
     let n_tracks = tracks.len();
     let rows = tracks
         .into_iter()
         .enumerate()
         .map(|(id, t)| {
-            let (id, style) = if playing_track_uri == t.id.uri() {
+            // only the marks of the focused track table are shown
+            let is_marked = is_active && ui.track_marks.is_marked(t, id, selected);
+            let (id, mut style) = if playing_track_uri == t.id.uri() {
                 (playing_id.to_string(), ui.theme.current_playing())
             } else {
                 ((id + 1).to_string(), Style::default())
             };
+            if is_marked {
+                style = style.patch(ui.theme.marked_row());
+            }
             Row::new(vec![
                 if data.user_data.is_liked_track(t) {
                     Cell::from(&configs.app_config.liked_icon as &str).style(ui.theme.like())
@@ -1021,18 +1283,23 @@ fn render_track_table(
         state: Some(state), ..
     } = ui.current_page_mut()
     {
-        let playable_table_state = match state {
+        let (window, playable_table_state) = match state {
             ContextPageUIState::Artist {
                 top_track_table, ..
-            } => top_track_table,
+            } => (
+                PageWindow::Artist(ArtistFocusState::TopTracks),
+                top_track_table,
+            ),
             ContextPageUIState::Playlist { track_table }
             | ContextPageUIState::Album { track_table }
-            | ContextPageUIState::Tracks { track_table } => track_table,
+            | ContextPageUIState::Tracks { track_table } => (PageWindow::Main, track_table),
             ContextPageUIState::Show { .. } => {
                 unreachable!("show's episode table should be handled by render_episode_table")
             }
         };
         utils::render_table_window(frame, track_table, rect, n_tracks, playable_table_state);
+        ui.page_windows
+            .push((window, WindowArea::table(rect, n_tracks)));
     }
 }
 
@@ -1113,5 +1380,7 @@ fn render_episode_table(
             s => unreachable!("unexpected state: {s:?}"),
         };
         utils::render_table_window(frame, episode_table, rect, n_episodes, playable_table_state);
+        ui.page_windows
+            .push((PageWindow::Main, WindowArea::table(rect, n_episodes)));
     }
 }
@@ -1,7 +1,7 @@
 use super::{
     config, utils::construct_and_render_block, Borders, Constraint, Frame, Gauge, Layout, Line,
-    LineGauge, Modifier, Paragraph, PlaybackMetadata, Rect, SharedState, Span, Style, Text,
-    UIStateGuard, Wrap,
+    LineGauge, Modifier, Paragraph, PlaybackMetadata, PlayerState, Rect, SharedState, Span, Style,
+    Text, UIStateGuard, Wrap,
 };
 #[cfg(feature = "image")]
 use crate::state::ImageRenderInfo;
@@ -127,7 +127,7 @@ pub fn render_playback_window(
             };
 
             if let Some(ref playback) = player.buffered_playback {
-                let playback_text = construct_playback_text(ui, state, item, playback);
+                let playback_text = construct_playback_text(ui, state, &player, item, playback);
                 let playback_desc = Paragraph::new(playback_text);
                 frame.render_widget(playback_desc, metadata_rect);
             }
@@ -190,6 +190,7 @@ fn clear_area(frame: &mut Frame, rect: Rect, theme: &config::Theme) {
 fn construct_playback_text(
     ui: &UIStateGuard,
     state: &SharedState,
+    player: &PlayerState,
     playable: &rspotify::model::PlayableItem,
     playback: &PlaybackMetadata,
 ) -> Text<'static> {
@@ -307,6 +308,39 @@ fn construct_playback_text(
                         "shuffle" => parts.push(format!("shuffle: {}", playback.shuffle_state)),
                         "volume" => parts.push(format!("volume: {volume_value}")),
                         "device" => parts.push(format!("device: {}", playback.device_name)),
+                        #[cfg(feature = "streaming")]
+                        "normalization" => {
+                            let device = &configs.app_config.device;
+                            // normalization is only applied by the integrated device
+                            if device.normalization && playback.device_name == device.name {
+                                parts.push(format!(
+                                    "normalization: {} ({})",
+                                    device.normalization_type.to_lowercase(),
+                                    device.normalization_method.to_lowercase()
+                                ));
+                            }
+                        }
+                        #[cfg(feature = "streaming")]
+                        // DSP presets are only applied by the integrated device
+                        "dsp_preset" if playback.device_name == configs.app_config.device.name => {
+                            if let Some(ref preset) = *state.dsp_preset.read() {
+                                parts.push(format!("dsp: {preset}"));
+                            }
+                        }
+                        "sleep_timer" => {
+                            if let Some(ref timer) = player.sleep_timer {
+                                parts.push(match timer.remaining(player, &data) {
+                                    Some(remaining) => format!(
+                                        "sleep: {}",
+                                        crate::utils::format_duration(
+                                            &chrono::Duration::from_std(remaining)
+                                                .unwrap_or_default()
+                                        )
+                                    ),
+                                    None => format!("sleep: {}", timer.mode),
+                                });
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -1,7 +1,7 @@
 use super::{
     config, utils, utils::construct_and_render_block, Borders, Cell, Constraint, Frame, Layout,
     Paragraph, PlaylistCreateCurrentField, PlaylistPopupAction, PopupState, Rect, Row, SharedState,
-    Table, UIStateGuard,
+    Table, UIStateGuard, WindowArea,
 };
 
 const SHORTCUT_TABLE_N_COLUMNS: usize = 3;
@@ -60,6 +60,48 @@ pub fn render_popup(
                 );
                 (chunks[0], true)
             }
+            PopupState::CommandPalette { query, entries, .. } => {
+                let chunks =
+                    Layout::vertical([Constraint::Fill(0), Constraint::Length(3)]).split(rect);
+
+                let input_rect = construct_and_render_block(
+                    "Command Palette",
+                    &ui.theme,
+                    Borders::ALL,
+                    frame,
+                    chunks[1],
+                );
+                frame.render_widget(query.widget(true), input_rect);
+
+                let items = crate::state::filter_items(entries, &query.get_text())
+                    .into_iter()
+                    .map(|e| (e.to_string(), false))
+                    .collect();
+                let rect = render_list_popup(frame, chunks[0], "Commands", items, 12, ui);
+                (rect, false)
+            }
+            PopupState::CommandLine(popup) => {
+                let chunks =
+                    Layout::vertical([Constraint::Fill(0), Constraint::Length(3)]).split(rect);
+
+                let title = match &popup.error {
+                    Some(err) => format!("Command Line: {err}"),
+                    None if popup.completions.len() > 1 => format!(
+                        "Command Line [{}/{}]",
+                        popup.completion_id + 1,
+                        popup.completions.len()
+                    ),
+                    None => "Command Line".to_string(),
+                };
+                let rect =
+                    construct_and_render_block(&title, &ui.theme, Borders::ALL, frame, chunks[1]);
+
+                let [prompt_rect, input_rect] =
+                    Layout::horizontal([Constraint::Length(1), Constraint::Fill(0)]).areas(rect);
+                frame.render_widget(Paragraph::new(":"), prompt_rect);
+                frame.render_widget(popup.input.widget(true), input_rect);
+                (chunks[0], true)
+            }
             PopupState::Search { query } => {
                 let chunks =
                     Layout::vertical([Constraint::Fill(0), Constraint::Length(3)]).split(rect);
@@ -107,13 +149,44 @@ pub fn render_popup(
                 let rect = render_list_popup(frame, rect, "Themes", items, 7, ui);
                 (rect, false)
             }
+            #[cfg(feature = "streaming")]
+            PopupState::DspPresetList(presets, ..) => {
+                let active_preset = state.dsp_preset.read();
+                let items = presets
+                    .iter()
+                    .map(|p| {
+                        (
+                            p.clone().unwrap_or_else(|| "Off".to_string()),
+                            *p == *active_preset,
+                        )
+                    })
+                    .collect();
+
+                let rect = render_list_popup(frame, rect, "DSP Presets", items, 7, ui);
+                (rect, false)
+            }
+            PopupState::SleepTimerList(modes, ..) => {
+                let active_mode = state.player.read().sleep_timer.as_ref().map(|t| t.mode);
+                let items = modes
+                    .iter()
+                    .map(|m| {
+                        (
+                            m.map_or_else(|| "Off".to_string(), |m| m.to_string()),
+                            *m == active_mode,
+                        )
+                    })
+                    .collect();
+
+                let rect = render_list_popup(frame, rect, "Sleep Timer", items, 10, ui);
+                (rect, false)
+            }
             PopupState::UserPlaylistList(action, _) => {
                 let data = state.data.read();
                 let items = match action {
                     PlaylistPopupAction::Browse { folder_id } => {
                         data.user_data.folder_playlists_items(*folder_id)
                     }
-                    PlaylistPopupAction::AddTrack { folder_id, .. }
+                    PlaylistPopupAction::AddTracks { folder_id, .. }
                     | PlaylistPopupAction::AddEpisode { folder_id, .. } => {
                         data.user_data.modifiable_playlist_items(Some(*folder_id))
                     }
@@ -180,6 +253,7 @@ fn render_list_popup(
         len,
         ui.popup.as_mut().unwrap().list_state_mut().unwrap(),
     );
+    ui.popup_window = Some(WindowArea::list(rect, len));
 
     chunks[0]
 }
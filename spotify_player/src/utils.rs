@@ -37,6 +37,19 @@ pub fn get_episode_show_image_url(episode: &rspotify::model::FullEpisode) -> Opt
     }
 }
 
+/// returns the name of a track's `.lrc` lyrics file, i.e `<artists> - <track>.lrc`,
+/// with characters that are invalid in a file name replaced by `_`
+pub fn lrc_file_name(track: &str, artists: &str) -> String {
+    format!("{artists} - {track}.lrc")
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
 pub fn parse_uri(uri: &str) -> Cow<str> {
     let parts = uri.split(':').collect::<Vec<_>>();
     // The below URI probably has a format of `spotify:user:{user_id}:{type}:{id}`,